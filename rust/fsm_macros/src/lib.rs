@@ -0,0 +1,349 @@
+//! Derives for the state machine framework of the game crate.
+//!
+//! The generated code names the framework by its path inside the game
+//! crate, `crate::common::states` and `crate::common::finite_state_machine`,
+//! so the derives only work inside that crate or a crate with the same
+//! module layout. States sharing their fields through `Rc<RefCell<_>>`
+//! hand borrow errors to `crate::common::states::report_state_error()`, so
+//! the generated code does not depend on the engine.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Error, Expr, Field, Fields, GenericArgument, Ident, Path, PathArguments,
+    PathSegment, Type, parse_macro_input, spanned::Spanned,
+};
+
+/// Generates the `StateBase` implementation for a state struct.
+///
+/// The struct needs a `#[state(name = StatesEnum::Variant)]` attribute and
+/// its fields are picked up from `#[state(...)]` annotations:
+///
+/// - `context`: the state's context, passed to `new()`
+/// - `next_state`: an `Option<StatesEnum>` or `Rc<RefCell<Option<StatesEnum>>>`
+/// - `active`: a `bool` or `Rc<RefCell<bool>>` toggled by `set_active()`
+/// - `release`: taken out of the state when `destroy()` is called
+///
+/// Every other field is created with `Default::default()` in `new()`.
+///
+/// Implements `crate::common::states::StateBase`, see the crate docs for
+/// the module layout this expects.
+#[proc_macro_derive(State, attributes(state))]
+pub fn derive_state(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand_state(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Generates `register_state()` for a `FiniteStateMachine` implementor.
+///
+/// The machine has to implement
+/// `crate::common::finite_state_machine::FiniteStateMachine`.
+#[proc_macro_derive(StateMachine)]
+pub fn derive_state_machine(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let machine = &input.ident;
+
+    quote! {
+        impl #machine {
+            #[allow(clippy::type_complexity)]
+            fn register_state(
+                &mut self,
+                state: Box<
+                    dyn crate::common::states::State<
+                        <Self as crate::common::finite_state_machine::FiniteStateMachine>::Input,
+                        Context = <Self as crate::common::finite_state_machine::FiniteStateMachine>::Context,
+                        StatesEnum = <Self as crate::common::finite_state_machine::FiniteStateMachine>::StatesEnum,
+                    >,
+                >,
+                states: &mut std::collections::HashMap<
+                    <Self as crate::common::finite_state_machine::FiniteStateMachine>::StatesEnum,
+                    Box<
+                        dyn crate::common::states::State<
+                            <Self as crate::common::finite_state_machine::FiniteStateMachine>::Input,
+                            Context = <Self as crate::common::finite_state_machine::FiniteStateMachine>::Context,
+                            StatesEnum = <Self as crate::common::finite_state_machine::FiniteStateMachine>::StatesEnum,
+                        >,
+                    >,
+                >,
+            ) {
+                let state_name = state.get_state_name();
+                states.insert(state_name, state);
+            }
+        }
+    }
+    .into()
+}
+
+#[derive(Default)]
+struct FieldFlags {
+    context: bool,
+    next_state: bool,
+    active: bool,
+    release: bool,
+}
+
+fn expand_state(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let state = &input.ident;
+    let name = parse_state_name(&input)?;
+    let states_enum = states_enum_path(&name)?;
+
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            input.span(),
+            "State can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new(input.span(), "State requires named fields"));
+    };
+
+    let mut context: Option<&Field> = None;
+    let mut next_state: Option<&Field> = None;
+    let mut active: Option<&Field> = None;
+    let mut released = vec![];
+    let mut initializers = vec![];
+
+    for field in fields.named.iter() {
+        let ident = field.ident.as_ref().expect("named field");
+        let flags = parse_field_flags(field)?;
+
+        if flags.context {
+            context = Some(field);
+            initializers.push(quote! { #ident: context });
+        } else {
+            initializers.push(quote! { #ident: Default::default() });
+        }
+
+        if flags.next_state {
+            next_state = Some(field);
+        }
+
+        if flags.active {
+            active = Some(field);
+        }
+
+        if flags.release {
+            released.push(ident);
+        }
+    }
+
+    let context =
+        context.ok_or_else(|| Error::new(input.span(), "missing a #[state(context)] field"))?;
+    let context_type = &context.ty;
+
+    let next_state = next_state
+        .ok_or_else(|| Error::new(input.span(), "missing a #[state(next_state)] field"))?;
+    let next_state_ident = &next_state.ident;
+
+    let (set_next_state, get_next_state) = if is_shared(next_state)? {
+        (
+            quote! {
+                let borrow = self
+                    .#next_state_ident
+                    .try_borrow_mut()
+                    .map_err(|_| crate::common::states::StateError::NextState);
+
+                match borrow {
+                    Ok(mut next_state) => *next_state = Some(state),
+                    Err(error) => crate::common::states::report_state_error(error),
+                }
+            },
+            quote! {
+                let borrow = self
+                    .#next_state_ident
+                    .try_borrow()
+                    .map_err(|_| crate::common::states::StateError::NextState);
+
+                match borrow {
+                    Ok(next_state) => next_state.clone(),
+                    Err(error) => {
+                        crate::common::states::report_state_error(error);
+                        None
+                    }
+                }
+            },
+        )
+    } else {
+        (
+            quote! { self.#next_state_ident = Some(state); },
+            quote! { self.#next_state_ident.clone() },
+        )
+    };
+
+    let active_methods = active.map(|field| -> syn::Result<TokenStream2> {
+        let ident = &field.ident;
+
+        Ok(if is_shared(field)? {
+            quote! {
+                fn set_active(&mut self, is_active: bool) {
+                    let borrow = self
+                        .#ident
+                        .try_borrow_mut()
+                        .map_err(|_| crate::common::states::StateError::ActiveFlag);
+
+                    match borrow {
+                        Ok(mut active) => *active = is_active,
+                        Err(error) => crate::common::states::report_state_error(error),
+                    }
+                }
+
+                fn is_active(&self) -> bool {
+                    match self.#ident.try_borrow() {
+                        Ok(active) => *active,
+                        Err(_) => false,
+                    }
+                }
+            }
+        } else {
+            quote! {
+                fn set_active(&mut self, is_active: bool) {
+                    self.#ident = is_active;
+                }
+
+                fn is_active(&self) -> bool {
+                    self.#ident
+                }
+            }
+        })
+    });
+    let active_methods = active_methods.transpose()?;
+
+    Ok(quote! {
+        impl crate::common::states::StateBase for #state {
+            type StatesEnum = #states_enum;
+            type Context = #context_type;
+
+            fn new(context: Self::Context) -> Self {
+                #state {
+                    #(#initializers,)*
+                }
+            }
+
+            fn get_state_name(&self) -> Self::StatesEnum {
+                #name
+            }
+
+            fn set_next_state(&mut self, state: Self::StatesEnum) {
+                #set_next_state
+            }
+
+            fn get_next_state(&mut self) -> Option<Self::StatesEnum> {
+                #get_next_state
+            }
+
+            #active_methods
+
+            fn destroy(&mut self) {
+                #(let _ = self.#released.take();)*
+            }
+        }
+    })
+}
+
+fn parse_state_name(input: &DeriveInput) -> syn::Result<Path> {
+    let mut name = None;
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("state"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                let value: Expr = meta.value()?.parse()?;
+                let Expr::Path(path) = value else {
+                    return Err(meta.error("expected a path like `StatesEnum::Variant`"));
+                };
+                name = Some(path.path);
+
+                Ok(())
+            } else {
+                Err(meta.error("unsupported state attribute"))
+            }
+        })?;
+    }
+
+    name.ok_or_else(|| {
+        Error::new(
+            input.span(),
+            "missing #[state(name = StatesEnum::Variant)] attribute",
+        )
+    })
+}
+
+fn states_enum_path(name: &Path) -> syn::Result<Path> {
+    if name.segments.len() < 2 {
+        return Err(Error::new(
+            name.span(),
+            "the state name needs to be an enum variant like `StatesEnum::Variant`",
+        ));
+    }
+
+    let segments_count = name.segments.len() - 1;
+    let mut states_enum = name.clone();
+    states_enum.segments = name.segments.iter().take(segments_count).cloned().collect();
+
+    Ok(states_enum)
+}
+
+fn parse_field_flags(field: &Field) -> syn::Result<FieldFlags> {
+    let mut flags = FieldFlags::default();
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("state"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let flag = match meta.path.get_ident().map(Ident::to_string).as_deref() {
+                Some("context") => &mut flags.context,
+                Some("next_state") => &mut flags.next_state,
+                Some("active") => &mut flags.active,
+                Some("release") => &mut flags.release,
+                _ => return Err(meta.error("unsupported state field attribute")),
+            };
+            *flag = true;
+
+            Ok(())
+        })?;
+    }
+
+    Ok(flags)
+}
+
+/// Whether the field is shared with signal listeners through an
+/// `Rc<RefCell<_>>`, any other `Rc` can not be written to
+fn is_shared(field: &Field) -> syn::Result<bool> {
+    let Some(rc) = last_segment(&field.ty).filter(|segment| segment.ident == "Rc") else {
+        return Ok(false);
+    };
+
+    let PathArguments::AngleBracketed(arguments) = &rc.arguments else {
+        return Ok(false);
+    };
+
+    let is_ref_cell = arguments.args.iter().any(|argument| {
+        matches!(argument, GenericArgument::Type(ty)
+            if last_segment(ty).is_some_and(|segment| segment.ident == "RefCell"))
+    });
+
+    match is_ref_cell {
+        true => Ok(true),
+        false => Err(Error::new(
+            field.ty.span(),
+            "shared state fields need to be an `Rc<RefCell<_>>`",
+        )),
+    }
+}
+
+fn last_segment(ty: &Type) -> Option<&PathSegment> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+
+    type_path.path.segments.last()
+}
@@ -0,0 +1,44 @@
+//! Stand-in for the `common` module of the game crate, the derives expand
+//! to paths inside it
+
+pub mod states {
+    #[derive(Debug)]
+    pub enum StateError {
+        NextState,
+        ActiveFlag,
+    }
+
+    pub fn report_state_error(error: StateError) {
+        panic!("{error:?}");
+    }
+
+    pub trait StateBase {
+        type StatesEnum;
+        type Context;
+
+        fn new(context: Self::Context) -> Self
+        where
+            Self: Sized;
+
+        fn get_state_name(&self) -> Self::StatesEnum;
+        fn set_next_state(&mut self, state: Self::StatesEnum);
+        fn get_next_state(&mut self) -> Option<Self::StatesEnum>;
+
+        fn set_active(&mut self, _is_active: bool) {}
+        fn is_active(&self) -> bool {
+            true
+        }
+
+        fn destroy(&mut self) {}
+    }
+
+    pub trait State<Input>: StateBase {}
+}
+
+pub mod finite_state_machine {
+    pub trait FiniteStateMachine {
+        type StatesEnum: std::hash::Hash + Eq;
+        type Context;
+        type Input;
+    }
+}
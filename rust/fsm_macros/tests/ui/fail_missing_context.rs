@@ -0,0 +1,14 @@
+use fsm_macros::State;
+
+enum States {
+    Idle,
+}
+
+#[derive(State)]
+#[state(name = States::Idle)]
+struct Idle {
+    #[state(next_state)]
+    next_state: Option<States>,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use fsm_macros::State;
+
+enum States {
+    Idle,
+}
+
+#[derive(State)]
+struct Idle {
+    #[state(context)]
+    context: (),
+    #[state(next_state)]
+    next_state: Option<States>,
+}
+
+fn main() {}
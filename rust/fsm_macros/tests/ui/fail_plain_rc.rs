@@ -0,0 +1,18 @@
+use std::rc::Rc;
+
+use fsm_macros::State;
+
+enum States {
+    Idle,
+}
+
+#[derive(State)]
+#[state(name = States::Idle)]
+struct Idle {
+    #[state(context)]
+    context: (),
+    #[state(next_state)]
+    next_state: Rc<Option<States>>,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use fsm_macros::State;
+
+enum States {
+    Idle,
+}
+
+#[derive(State)]
+#[state(name = States::Idle)]
+struct Idle {
+    #[state(context)]
+    context: (),
+    #[state(next_state)]
+    next_state: Option<States>,
+    #[state(timer)]
+    elapsed: f32,
+}
+
+fn main() {}
@@ -0,0 +1,38 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc};
+
+use common::states::StateBase;
+use fsm_macros::State;
+
+#[derive(Debug, Clone, PartialEq)]
+enum States {
+    Idle,
+    Hover,
+}
+
+#[derive(State)]
+#[state(name = States::Idle)]
+struct Idle {
+    #[state(context)]
+    context: (),
+    #[state(next_state)]
+    next_state: Rc<RefCell<Option<States>>>,
+    #[state(active)]
+    active: Rc<RefCell<bool>>,
+}
+
+fn main() {
+    let mut idle = Idle::new(());
+    let listener_next_state = idle.next_state.clone();
+
+    *listener_next_state.borrow_mut() = Some(States::Hover);
+    assert_eq!(idle.get_next_state(), Some(States::Hover));
+
+    idle.set_next_state(States::Idle);
+    assert_eq!(*listener_next_state.borrow(), Some(States::Idle));
+
+    idle.set_active(true);
+    assert!(idle.is_active());
+    assert!(*idle.active.borrow());
+}
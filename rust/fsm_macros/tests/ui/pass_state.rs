@@ -0,0 +1,43 @@
+mod common;
+
+use common::states::StateBase;
+use fsm_macros::State;
+
+#[derive(Debug, Clone, PartialEq)]
+enum States {
+    Idle,
+    Walking,
+}
+
+#[derive(State)]
+#[state(name = States::Idle)]
+struct Idle {
+    #[state(context)]
+    context: u32,
+    #[state(next_state)]
+    next_state: Option<States>,
+    #[state(active)]
+    active: bool,
+    #[state(release)]
+    listener: Option<String>,
+    elapsed: f32,
+}
+
+fn main() {
+    let mut idle = Idle::new(7);
+    assert_eq!(idle.context, 7);
+    assert_eq!(idle.elapsed, 0.);
+    assert_eq!(idle.get_state_name(), States::Idle);
+
+    assert_eq!(idle.get_next_state(), None);
+    idle.set_next_state(States::Walking);
+    assert_eq!(idle.get_next_state(), Some(States::Walking));
+
+    assert!(!idle.is_active());
+    idle.set_active(true);
+    assert!(idle.is_active());
+
+    idle.listener = Some("listener".to_string());
+    idle.destroy();
+    assert_eq!(idle.listener, None);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::{
+    finite_state_machine::FiniteStateMachine,
+    states::{State, StateBase},
+};
+use fsm_macros::{State, StateMachine};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum States {
+    Idle,
+}
+
+#[derive(State)]
+#[state(name = States::Idle)]
+struct Idle {
+    #[state(context)]
+    context: (),
+    #[state(next_state)]
+    next_state: Option<States>,
+}
+
+impl State<()> for Idle {}
+
+type StateMap = HashMap<States, Box<dyn State<(), Context = (), StatesEnum = States>>>;
+
+#[derive(StateMachine)]
+struct Machine;
+
+impl FiniteStateMachine for Machine {
+    type StatesEnum = States;
+    type Context = ();
+    type Input = ();
+}
+
+fn main() {
+    let mut states: StateMap = HashMap::new();
+    Machine.register_state(Box::new(Idle::new(())), &mut states);
+
+    assert!(states.contains_key(&States::Idle));
+}
@@ -1,9 +1,23 @@
+use std::sync::LazyLock;
+
+use godot::{builtin::Vector2, classes::Input, obj::Gd};
+
+/// The action set of the game, the InputBindings node rebinds these
+pub static ACTIONS: LazyLock<Actions> = LazyLock::new(Actions::default);
+
 pub struct Actions {
     pub forward: &'static str,
     pub backward: &'static str,
     pub left: &'static str,
     pub right: &'static str,
+    pub look_left: &'static str,
+    pub look_right: &'static str,
+    pub look_up: &'static str,
+    pub look_down: &'static str,
     pub jump: &'static str,
+    pub sprint: &'static str,
+    pub crouch: &'static str,
+    pub dodge: &'static str,
     pub mouse_mode: &'static str,
 }
 
@@ -14,8 +28,59 @@ impl Default for Actions {
             backward: "move_backward",
             left: "move_left",
             right: "move_right",
+            look_left: "look_left",
+            look_right: "look_right",
+            look_up: "look_up",
+            look_down: "look_down",
             jump: "jump",
+            sprint: "sprint",
+            crouch: "crouch",
+            dodge: "dodge",
             mouse_mode: "mouse_mode",
         }
     }
 }
+
+impl Actions {
+    /// Every action name
+    pub fn all(&self) -> [&'static str; 13] {
+        [
+            self.forward,
+            self.backward,
+            self.left,
+            self.right,
+            self.look_left,
+            self.look_right,
+            self.look_up,
+            self.look_down,
+            self.jump,
+            self.sprint,
+            self.crouch,
+            self.dodge,
+            self.mouse_mode,
+        ]
+    }
+
+    /// Movement actions as a vector, x is left and y is forward.
+    ///
+    /// Keeps the analog magnitude of a partially tilted stick, the length
+    /// is at most 1 so keyboard diagonals are not faster.
+    pub fn movement_vector(&self, input: &Gd<Input>) -> Vector2 {
+        input.get_vector(self.right, self.left, self.backward, self.forward)
+    }
+
+    /// Look actions as a vector in screen space, x is right and y is down
+    /// like mouse motion. Stick values within `deadzone` are ignored and the
+    /// rest is rescaled to start at 0.
+    pub fn look_vector(&self, input: &Gd<Input>, deadzone: f32) -> Vector2 {
+        input
+            .get_vector_ex(
+                self.look_left,
+                self.look_right,
+                self.look_up,
+                self.look_down,
+            )
+            .deadzone(deadzone)
+            .done()
+    }
+}
@@ -1,7 +1,10 @@
 pub mod camera;
 pub mod finite_state_machine;
+pub mod footsteps;
+pub mod input_bindings;
 pub mod inventory;
 pub mod mouse;
 pub mod proximity_detector;
 pub mod states;
 pub mod ui;
+pub mod water_volume;
@@ -1,11 +1,13 @@
 use godot::{
-    classes::{INode3D, Input, InputEvent, InputEventMouseMotion, Node3D, input::MouseMode},
+    classes::{Curve, INode3D, Input, InputEvent, InputEventMouseMotion, Node3D, input::MouseMode},
     obj::Gd,
     prelude::{GodotClass, godot_api},
 };
 
 use godot::prelude::*;
 
+use crate::actions::ACTIONS;
+
 #[derive(GodotClass)]
 #[class(base=Node3D, init)]
 #[allow(unused)]
@@ -27,11 +29,45 @@ pub struct Camera {
     #[export]
     max_x_angle: f32,
 
+    #[export(range=(0.0, 10.0))]
+    /// Radians per second the look stick rotates the camera at full tilt
+    stick_sensitivity: Vector2,
+
+    #[export(range=(0.0, 0.9))]
+    /// Look stick tilt below which the camera does not rotate
+    stick_deadzone: f32,
+
+    #[export]
+    /// Maps look stick tilt past the deadzone to rotation speed, linear if
+    /// not set
+    stick_response_curve: Option<Gd<Curve>>,
+
     accumulated_rotation: Vector2,
 }
 
 #[godot_api]
 impl INode3D for Camera {
+    fn process(&mut self, delta: f64) {
+        // NOTE: The look stick works without the mouse being captured
+        let input = Input::singleton();
+        let look = ACTIONS.look_vector(&input, self.stick_deadzone);
+
+        if look == Vector2::ZERO {
+            return;
+        }
+
+        let strength = look.length();
+        let response = self
+            .stick_response_curve
+            .as_ref()
+            .map_or(strength, |curve| curve.sample(strength));
+
+        self.accumulated_rotation +=
+            look / strength * response * self.stick_sensitivity * delta as f32;
+
+        self.apply_rotation();
+    }
+
     // Handle user input.
     fn input(&mut self, event: Gd<InputEvent>) {
         let input = Input::singleton();
@@ -49,29 +85,40 @@ impl INode3D for Camera {
 
                 self.accumulated_rotation += relative * self.rotation_speed;
 
-                self.base_mut().set_basis(Basis::default());
-
-                // let y = self
-                //     .accumulated_rotation
-                //     .x
-                //     .clamp(self.min_y_angle, self.max_y_angle);
-
-                let y = self.accumulated_rotation.x;
-                let x = self
-                    .accumulated_rotation
-                    .y
-                    .clamp(self.min_x_angle, self.max_x_angle);
-
-                // godot_print!(
-                //     "setting rotation: x: {x}, y: {y}, accumulated_rotation: {}",
-                //     self.accumulated_rotation
-                // );
-
-                self.base_mut().rotate_object_local(Vector3::UP, y);
-                self.base_mut().rotate_object_local(Vector3::LEFT, x);
+                self.apply_rotation();
             }
 
             _ => {}
         }
     }
 }
+
+impl Camera {
+    /// Rebuilds the basis from the accumulated rotation
+    fn apply_rotation(&mut self) {
+        // NOTE: Keeps the pitch from winding up past the limits, otherwise
+        // it has to be undone before the camera moves back
+        self.accumulated_rotation.y = self
+            .accumulated_rotation
+            .y
+            .clamp(self.min_x_angle, self.max_x_angle);
+
+        self.base_mut().set_basis(Basis::default());
+
+        // let y = self
+        //     .accumulated_rotation
+        //     .x
+        //     .clamp(self.min_y_angle, self.max_y_angle);
+
+        let y = self.accumulated_rotation.x;
+        let x = self.accumulated_rotation.y;
+
+        // godot_print!(
+        //     "setting rotation: x: {x}, y: {y}, accumulated_rotation: {}",
+        //     self.accumulated_rotation
+        // );
+
+        self.base_mut().rotate_object_local(Vector3::UP, y);
+        self.base_mut().rotate_object_local(Vector3::LEFT, x);
+    }
+}
@@ -1,46 +1,100 @@
 #![allow(non_snake_case, unused)]
 
-use std::{collections::HashMap, fmt::Debug, hash::Hash};
+//! Engine independent state machine core, Godot nodes hook into it
+//! through the adapter in `godot_adapter`
 
-use godot::{
-    classes::{INode3D, InputEvent},
-    global::godot_print,
-    obj::Gd,
+pub mod godot_adapter;
+pub mod regions;
+pub mod trace;
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::{Debug, Display},
+    hash::Hash,
+    time::Duration,
 };
 
-use super::states::State;
+use thiserror::Error;
 
-const STATE_ERROR: &str = "should_transition should always return state";
+use regions::{OrthogonalRegion, RegionEvent, StateSnapshot};
+use trace::{TransitionReason, TransitionTrace};
 
-#[macro_export]
-macro_rules! impl_inode3d_for_fsm {
-    ($machine: ident) => {
-        #[godot_api]
-        impl godot::classes::INode3D for $machine {
-            fn ready(&mut self) {
-                $crate::common::finite_state_machine::FiniteStateMachine::ready(self);
-            }
+use super::states::{State, StateBase};
 
-            fn input(&mut self, event: godot::obj::Gd<godot::classes::InputEvent>) {
-                $crate::common::finite_state_machine::FiniteStateMachine::input(self, event);
-            }
+#[derive(Debug, Error)]
+pub enum TransitionError<S: Debug> {
+    #[error("Unable to get state: {0:?}")]
+    MissingState(S),
+    #[error("There is no suspended state to resume")]
+    EmptyStateStack,
+}
 
-            fn process(&mut self, delta: f64) {
-                $crate::common::finite_state_machine::FiniteStateMachine::process(self, delta);
-            }
+/// Which update callback advances the state timer of a machine
+#[derive(Debug, PartialEq, Eq)]
+pub enum TimerTick {
+    Process,
+    PhysicsProcess,
+}
 
-            fn physics_process(&mut self, delta: f64) {
-                $crate::common::finite_state_machine::FiniteStateMachine::physics_process(
-                    self, delta,
-                );
-            }
-        }
-    };
+/// Time spent in the current state, reset on every state change except
+/// a pop which resumes the time of the suspended state
+#[derive(Debug, Default)]
+pub struct StateTimer {
+    elapsed: Duration,
+    /// Elapsed time of the suspended states, in the order of the state stack
+    suspended: Vec<Duration>,
 }
 
-pub trait FiniteStateMachine: Debug + Sized {
-    type StatesEnum: PartialEq + Eq + Hash + Debug;
+impl StateTimer {
+    pub fn advance(&mut self, delta: f64) {
+        self.elapsed += Duration::from_secs_f64(delta.max(0.));
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Saves the elapsed time of a state that is pushed down and restarts
+    /// the timer for the state on top of it
+    pub fn suspend(&mut self) {
+        self.suspended.push(self.elapsed);
+        self.reset();
+    }
+
+    /// Continues with the elapsed time the resumed state was suspended with
+    pub fn resume(&mut self) {
+        self.elapsed = self.suspended.pop().unwrap_or_default();
+    }
+
+    /// Drops the saved times once the suspended states are exited
+    pub fn clear_suspended(&mut self) {
+        self.suspended.clear();
+    }
+}
+
+/// Forwards state changes to the engine, Godot machines get an
+/// implementation emitting signals from `impl_inode3d_for_fsm!`
+pub trait StateSignals {
+    fn emit_state_entered(&mut self, name: &str);
+    fn emit_state_exited(&mut self, name: &str);
+    fn emit_transition(&mut self, from: &str, to: &str);
+    fn emit_transition_error(&mut self, error: &str);
+    fn emit_finished(&mut self, state: &str);
+}
+
+pub trait FiniteStateMachine: Debug + Sized + StateSignals {
+    type StatesEnum: PartialEq + Eq + Hash + Clone + Debug + Display;
     type Context;
+    type Input: Clone;
+
+    /// Only one callback feeds the state timer so time is never counted twice
+    const TIMER_TICK: TimerTick = TimerTick::PhysicsProcess;
+    /// Name of the machine's own states in a `StateSnapshot`
+    const REGION_NAME: &'static str = "main";
 
     fn ready(&mut self);
     #[allow(clippy::type_complexity)]
@@ -49,30 +103,159 @@ pub trait FiniteStateMachine: Debug + Sized {
         context: Self::Context,
     ) -> HashMap<
         Self::StatesEnum,
-        Box<dyn State<Context = Self::Context, StatesEnum = Self::StatesEnum>>,
+        Box<dyn State<Self::Input, Context = Self::Context, StatesEnum = Self::StatesEnum>>,
     >;
     fn get_current_state(&self) -> Self::StatesEnum;
     fn set_current_state(&mut self, state: Self::StatesEnum);
     fn set_transitioning(&mut self, in_transition: bool);
     fn get_transitioning(&self) -> bool;
+    /// States suspended by `push_state()`, the last one is resumed first
+    fn get_state_stack(&mut self) -> &mut Vec<Self::StatesEnum>;
+    fn get_state_timer(&mut self) -> &mut StateTimer;
     #[allow(clippy::type_complexity)]
     fn get_states_map(
         &mut self,
     ) -> &mut HashMap<
         Self::StatesEnum,
-        Box<dyn State<Context = Self::Context, StatesEnum = Self::StatesEnum>>,
+        Box<dyn State<Self::Input, Context = Self::Context, StatesEnum = Self::StatesEnum>>,
     >;
 
     #[allow(clippy::type_complexity)]
     fn get_state(
         &mut self,
         state: &Self::StatesEnum,
-    ) -> Option<&mut Box<dyn State<Context = Self::Context, StatesEnum = Self::StatesEnum>>> {
+    ) -> Option<
+        &mut Box<dyn State<Self::Input, Context = Self::Context, StatesEnum = Self::StatesEnum>>,
+    > {
         let state_map = self.get_states_map();
         state_map.get_mut(state)
     }
 
-    fn input(&mut self, event: Gd<InputEvent>) {
+    /// States that end the machine, entering one tears the machine down
+    /// through `finish()` instead of entering a registered state
+    fn is_final_state(&self, _state: &Self::StatesEnum) -> bool {
+        false
+    }
+
+    fn is_finished(&self) -> bool {
+        self.is_final_state(&self.get_current_state())
+    }
+
+    /// Drops the machine's hold on its context, called by `finish()`
+    /// after every state was destroyed
+    fn release_context(&mut self) {}
+
+    /// Frees the machine itself, the last step of `finish()`
+    fn free_machine(&mut self) {}
+
+    /// Transition history, machines without a trace return `None`
+    fn get_trace(&mut self) -> Option<&mut TransitionTrace> {
+        None
+    }
+
+    fn trace_transition(
+        &mut self,
+        from: &Self::StatesEnum,
+        to: &Self::StatesEnum,
+        reason: TransitionReason,
+    ) {
+        if let Some(trace) = self.get_trace() {
+            trace.record(from.to_string(), to.to_string(), reason);
+        }
+    }
+
+    /// Orthogonal regions running next to the machine's own states
+    #[allow(clippy::type_complexity)]
+    fn get_regions(
+        &mut self,
+    ) -> Option<&mut Vec<Box<dyn OrthogonalRegion<Self::Input, Self::Context>>>> {
+        None
+    }
+
+    /// Hands the machine's context to its regions, called next to
+    /// `setup_states()` so both build their states from it
+    fn start_regions(&mut self, context: Self::Context)
+    where
+        Self::Context: Clone,
+    {
+        if let Some(regions) = self.get_regions() {
+            regions
+                .iter_mut()
+                .for_each(|region| region.start(context.clone()));
+        }
+    }
+
+    /// Current state of the machine and each of its regions
+    fn get_state_snapshot(&mut self) -> StateSnapshot {
+        let mut snapshot = StateSnapshot::default();
+        snapshot.insert(Self::REGION_NAME, self.get_current_state().to_string());
+
+        if let Some(regions) = self.get_regions() {
+            regions.iter().for_each(|region| {
+                snapshot.insert(region.name(), region.current_state_name());
+            });
+        }
+
+        snapshot
+    }
+
+    fn update_regions(&mut self, delta: f64, tick: TimerTick) {
+        let snapshot = self.get_state_snapshot();
+        let Some(regions) = self.get_regions() else {
+            return;
+        };
+
+        let mut events = vec![];
+        for region in regions.iter_mut() {
+            region.update_snapshot(snapshot.clone());
+
+            match tick {
+                TimerTick::Process => region.process(delta),
+                TimerTick::PhysicsProcess => region.physics_process(delta),
+            }
+
+            let name = region.name().to_string();
+            events.extend(
+                region
+                    .drain_events()
+                    .into_iter()
+                    .map(|event| (name.clone(), event)),
+            );
+        }
+
+        for (region, event) in events {
+            match event {
+                RegionEvent::Entered(state) => {
+                    self.emit_state_entered(&format!("{region}/{state}"))
+                }
+                RegionEvent::Exited(state) => self.emit_state_exited(&format!("{region}/{state}")),
+                RegionEvent::Transition(from, to) => {
+                    self.emit_transition(&format!("{region}/{from}"), &format!("{region}/{to}"))
+                }
+                RegionEvent::TransitionError(error) => {
+                    self.emit_transition_error(&format!("{region}:: {error}"))
+                }
+            }
+        }
+    }
+
+    /// Cross region guard, returning false keeps the machine in `from`
+    /// and the transition is tried again on the next update
+    fn can_transition(&mut self, _from: &Self::StatesEnum, _to: &Self::StatesEnum) -> bool {
+        true
+    }
+
+    fn input(&mut self, event: Self::Input) {
+        if self.is_finished() {
+            return;
+        }
+
+        if let Some(regions) = self.get_regions() {
+            regions
+                .iter_mut()
+                .for_each(|region| region.input(event.clone()));
+        }
+
         let state = self.get_current_state();
         let Some(current_state) = self.get_state(&state) else {
             return;
@@ -81,31 +264,103 @@ pub trait FiniteStateMachine: Debug + Sized {
         current_state.input(event);
     }
 
+    /// Runs every frame before the current state, for bookkeeping that
+    /// every state of the machine relies on
+    fn before_process(&mut self, _delta: f64) {}
+
+    /// `before_process()` for the physics frame
+    fn before_physics_process(&mut self, _delta: f64) {}
+
+    /// Called when the machine leaves the scene tree, e.g. when the game
+    /// quits
+    fn exit_tree(&mut self) {}
+
     fn process(&mut self, delta: f64) {
+        if self.is_finished() {
+            return;
+        }
+
+        if Self::TIMER_TICK == TimerTick::Process {
+            self.get_state_timer().advance(delta);
+
+            if let Some(trace) = self.get_trace() {
+                trace.tick();
+            }
+        }
+
+        self.before_process(delta);
+        self.update_regions(delta, TimerTick::Process);
+
+        if self.should_pop() {
+            if let Err(error) = self.pop_state() {
+                self.on_transition_error(error);
+            }
+
+            return;
+        }
+
         match self.should_transition() {
-            (true, next_state, _) => self.transition_to_state(next_state.expect(STATE_ERROR)),
+            (true, Some((next_state, reason)), _) => {
+                if let Err(error) = self.transition_with_reason(next_state, reason) {
+                    self.on_transition_error(error);
+                }
+            }
             (false, _, Some(current_state)) => current_state.process(delta as f32),
-            (false, _, None) => {}
+            _ => {}
         }
     }
 
     fn physics_process(&mut self, delta: f64) {
+        if self.is_finished() {
+            return;
+        }
+
+        if Self::TIMER_TICK == TimerTick::PhysicsProcess {
+            self.get_state_timer().advance(delta);
+
+            if let Some(trace) = self.get_trace() {
+                trace.tick();
+            }
+        }
+
+        self.before_physics_process(delta);
+        self.update_regions(delta, TimerTick::PhysicsProcess);
+
+        if self.should_pop() {
+            if let Err(error) = self.pop_state() {
+                self.on_transition_error(error);
+            }
+
+            return;
+        }
+
         match self.should_transition() {
-            (true, next_state, _) => self.transition_to_state(next_state.expect(STATE_ERROR)),
+            (true, Some((next_state, reason)), _) => {
+                if let Err(error) = self.transition_with_reason(next_state, reason) {
+                    self.on_transition_error(error);
+                }
+            }
             (false, _, Some(current_state)) => current_state.physics_process(delta as f32),
-            (false, _, None) => {}
+            _ => {}
         }
     }
 
+    /// Called when a transition failed, the request was rolled back and the
+    /// machine stays in the state it was in before the transition started
+    fn on_transition_error(&mut self, error: TransitionError<Self::StatesEnum>) {
+        self.emit_transition_error(&error.to_string());
+    }
+
     #[allow(clippy::type_complexity)]
     fn should_transition(
         &mut self,
     ) -> (
         bool,
-        Option<Self::StatesEnum>,
+        Option<(Self::StatesEnum, TransitionReason)>,
         Option<
             &mut Box<
                 dyn State<
+                        <Self as FiniteStateMachine>::Input,
                         StatesEnum = <Self as FiniteStateMachine>::StatesEnum,
                         Context = <Self as FiniteStateMachine>::Context,
                     >,
@@ -114,42 +369,1060 @@ pub trait FiniteStateMachine: Debug + Sized {
     ) {
         let state = self.get_current_state();
         let transitioning = self.get_transitioning();
-        let Some(current_state) = self.get_state(&state) else {
-            return (false, None, None);
+        let elapsed = self.get_state_timer().elapsed();
+        let next_state = {
+            let Some(current_state) = self.get_state(&state) else {
+                return (false, None, None);
+            };
+
+            if elapsed < current_state.min_dwell() {
+                None
+            } else {
+                // NOTE: A requested next state wins over the after() timeout
+                current_state
+                    .get_next_state()
+                    .filter(|new_state| state != *new_state)
+                    .map(|new_state| (new_state, TransitionReason::Requested))
+                    .or_else(|| {
+                        current_state
+                            .after()
+                            .filter(|(duration, _)| elapsed >= *duration)
+                            .map(|(_, timeout_state)| (timeout_state, TransitionReason::Timeout))
+                    })
+            }
         };
 
-        let next_state = current_state.get_next_state();
-        match &next_state {
-            Some(new_state) if !transitioning && state != *new_state => (true, next_state, None),
-            Some(_) | None => (false, None, Some(current_state)),
+        let allowed = match &next_state {
+            Some((new_state, _)) if !transitioning && state != *new_state => {
+                self.can_transition(&state, new_state)
+            }
+            Some(_) | None => false,
+        };
+
+        if allowed {
+            return (true, next_state, None);
         }
+
+        (false, None, self.get_state(&state))
     }
 
-    fn transition_to_state(&mut self, next_state: Self::StatesEnum) {
-        self.set_transitioning(true);
+    fn should_pop(&mut self) -> bool {
+        if self.get_transitioning() || self.get_state_stack().is_empty() {
+            return false;
+        }
+
+        let elapsed = self.get_state_timer().elapsed();
         let state = self.get_current_state();
+        self.get_state(&state).is_some_and(|current_state| {
+            elapsed >= current_state.min_dwell() && current_state.should_pop()
+        })
+    }
 
-        let Some(current_state) = self.get_state(&state) else {
-            godot_print!(
-                "FiniteStateMachine::transition_to_state():: Unable to get state: {:?}",
-                state
-            );
-            return;
-        };
+    /// Time spent in the current state
+    fn get_state_elapsed(&mut self) -> Duration {
+        self.get_state_timer().elapsed()
+    }
 
-        current_state.exit();
-        self.set_current_state(next_state);
+    /// Exits the current state and enters `next_state`, if `next_state`
+    /// does not exist the request is rolled back and the current state
+    /// keeps running
+    fn transition_to_state(
+        &mut self,
+        next_state: Self::StatesEnum,
+    ) -> Result<(), TransitionError<Self::StatesEnum>> {
+        self.transition_with_reason(next_state, TransitionReason::Requested)
+    }
 
-        let state = self.get_current_state();
-        let Some(current_state) = self.get_state(&state) else {
-            godot_print!(
-                "FiniteStateMachine::transition_to_state():: Unable to get state: {:?}",
-                state
-            );
-            return;
+    /// `transition_to_state()` recording why the transition happened
+    fn transition_with_reason(
+        &mut self,
+        next_state: Self::StatesEnum,
+        reason: TransitionReason,
+    ) -> Result<(), TransitionError<Self::StatesEnum>> {
+        if self.is_final_state(&next_state) {
+            self.finish(next_state, reason);
+
+            return Ok(());
+        }
+
+        let is_pushdown = self
+            .get_state(&next_state)
+            .is_some_and(|state| state.is_pushdown());
+
+        if is_pushdown {
+            return self.push_state(next_state);
+        }
+
+        let previous_state = self.get_current_state();
+        if self.get_state(&next_state).is_none() {
+            self.reject_transition(&previous_state, &next_state, reason);
+
+            return Err(TransitionError::MissingState(next_state));
+        }
+
+        self.set_transitioning(true);
+        self.get_state_timer().reset();
+
+        if let Some(current_state) = self.get_state(&previous_state) {
+            current_state.exit();
+            self.emit_state_exited(&previous_state.to_string());
+        }
+
+        self.set_current_state(next_state.clone());
+        self.unwind_state_stack();
+        if let Some(current_state) = self.get_state(&next_state) {
+            current_state.enter();
+        }
+
+        self.emit_state_entered(&next_state.to_string());
+        self.emit_transition(&previous_state.to_string(), &next_state.to_string());
+        self.trace_transition(&previous_state, &next_state, reason);
+
+        self.set_transitioning(false);
+
+        Ok(())
+    }
+
+    /// Ordered teardown once a final state is reached: exits the current
+    /// and suspended states, destroys every state, releases the context
+    /// and frees the machine after `finished` was emitted
+    fn finish(&mut self, final_state: Self::StatesEnum, reason: TransitionReason) {
+        self.set_transitioning(true);
+        let previous_state = self.get_current_state();
+
+        if let Some(current_state) = self.get_state(&previous_state) {
+            current_state.exit();
+            self.emit_state_exited(&previous_state.to_string());
+        }
+        self.unwind_state_stack();
+
+        let mut states = std::mem::take(self.get_states_map());
+        states.values_mut().for_each(|state| state.destroy());
+        drop(states);
+
+        self.release_context();
+
+        self.set_current_state(final_state.clone());
+        self.emit_transition(&previous_state.to_string(), &final_state.to_string());
+        self.trace_transition(&previous_state, &final_state, reason);
+        self.set_transitioning(false);
+
+        self.emit_finished(&final_state.to_string());
+        self.free_machine();
+    }
+
+    /// Suspends the current state and enters `next_state` on top of it,
+    /// the suspended state keeps its data and elapsed time until
+    /// `pop_state()` resumes it
+    fn push_state(
+        &mut self,
+        next_state: Self::StatesEnum,
+    ) -> Result<(), TransitionError<Self::StatesEnum>> {
+        let previous_state = self.get_current_state();
+        if self.get_state(&next_state).is_none() {
+            self.reject_transition(&previous_state, &next_state, TransitionReason::Push);
+
+            return Err(TransitionError::MissingState(next_state));
+        }
+
+        if self.get_state(&previous_state).is_none() {
+            return Err(TransitionError::MissingState(previous_state));
+        }
+
+        self.set_transitioning(true);
+        if let Some(current_state) = self.get_state(&previous_state) {
+            current_state.paused();
+        }
+        self.get_state_timer().suspend();
+        self.get_state_stack().push(previous_state.clone());
+        self.set_current_state(next_state.clone());
+
+        if let Some(current_state) = self.get_state(&next_state) {
+            current_state.enter();
+        }
+
+        self.emit_state_entered(&next_state.to_string());
+        self.emit_transition(&previous_state.to_string(), &next_state.to_string());
+        self.trace_transition(&previous_state, &next_state, TransitionReason::Push);
+
+        self.set_transitioning(false);
+
+        Ok(())
+    }
+
+    /// Exits the current state and resumes the state it interrupted
+    fn pop_state(&mut self) -> Result<(), TransitionError<Self::StatesEnum>> {
+        let Some(resumed_state) = self.get_state_stack().last().cloned() else {
+            return Err(TransitionError::EmptyStateStack);
         };
-        current_state.enter();
+
+        let previous_state = self.get_current_state();
+        if self.get_state(&resumed_state).is_none() {
+            self.trace_transition(&previous_state, &resumed_state, TransitionReason::Failed);
+
+            return Err(TransitionError::MissingState(resumed_state));
+        }
+
+        self.set_transitioning(true);
+        self.get_state_stack().pop();
+        self.get_state_timer().resume();
+
+        if let Some(current_state) = self.get_state(&previous_state) {
+            current_state.exit();
+            self.emit_state_exited(&previous_state.to_string());
+        }
+
+        self.set_current_state(resumed_state.clone());
+
+        if let Some(current_state) = self.get_state(&resumed_state) {
+            current_state.resumed();
+        }
+        self.emit_state_entered(&resumed_state.to_string());
+
+        self.emit_transition(&previous_state.to_string(), &resumed_state.to_string());
+        self.trace_transition(&previous_state, &resumed_state, TransitionReason::Pop);
 
         self.set_transitioning(false);
+
+        Ok(())
+    }
+
+    /// Rolls back the request of the current state for a `next_state` that
+    /// does not exist, so the state keeps running instead of requesting it
+    /// again on every frame
+    fn reject_transition(
+        &mut self,
+        current_state: &Self::StatesEnum,
+        next_state: &Self::StatesEnum,
+        reason: TransitionReason,
+    ) {
+        if let Some(state) = self.get_state(current_state) {
+            state.set_next_state(current_state.clone());
+        }
+
+        // NOTE: Restarts the after() timeout instead of firing it again
+        if reason == TransitionReason::Timeout {
+            self.get_state_timer().reset();
+        }
+
+        self.trace_transition(current_state, next_state, TransitionReason::Failed);
+    }
+
+    /// Exits every suspended state, used when a regular transition
+    /// replaces a pushed state instead of popping back
+    fn unwind_state_stack(&mut self) {
+        let stack = std::mem::take(self.get_state_stack());
+        self.get_state_timer().clear_suspended();
+
+        for state in stack.into_iter().rev() {
+            if let Some(paused_state) = self.get_state(&state) {
+                paused_state.exit();
+                self.emit_state_exited(&state.to_string());
+            }
+        }
+    }
+
+    /// Graphviz DOT graph of the declared transitions and `after()` timeouts,
+    /// transitions only seen in the trace are drawn dashed
+    fn to_dot(&mut self) -> String {
+        let type_name = std::any::type_name::<Self>();
+        let type_name = type_name.split('<').next().unwrap_or(type_name);
+        let machine = type_name.rsplit("::").next().unwrap_or(type_name);
+        let current = self.get_current_state();
+
+        let mut nodes = BTreeSet::new();
+        let mut edges = BTreeSet::new();
+        let mut targets = vec![];
+        for (name, state) in self.get_states_map().iter() {
+            let shape = if state.is_pushdown() {
+                "box"
+            } else {
+                "ellipse"
+            };
+            let style = if *name == current { "bold" } else { "solid" };
+            nodes.insert(format!("    \"{name}\" [shape={shape}, style={style}];\n"));
+
+            for next_state in state.transitions() {
+                edges.insert((name.to_string(), next_state.to_string(), String::new()));
+                targets.push(next_state);
+            }
+
+            if let Some((duration, timeout_state)) = state.after() {
+                let label = format!("after {:.2}s", duration.as_secs_f64());
+                edges.insert((name.to_string(), timeout_state.to_string(), label));
+            }
+        }
+
+        for state in targets {
+            if self.is_final_state(&state) {
+                nodes.insert(format!(
+                    "    \"{state}\" [shape=doublecircle, style=solid];\n"
+                ));
+            }
+        }
+
+        let mut traced = BTreeSet::new();
+        if let Some(trace) = self.get_trace() {
+            for record in trace.records() {
+                let declared = edges
+                    .iter()
+                    .any(|(from, to, _)| *from == record.from && *to == record.to);
+
+                if !declared && record.reason != TransitionReason::Failed {
+                    traced.insert((record.from.clone(), record.to.clone()));
+                }
+            }
+        }
+
+        let mut dot = format!("digraph {machine} {{\n    rankdir=LR;\n");
+        nodes.into_iter().for_each(|node| dot.push_str(&node));
+        for (from, to, label) in edges {
+            match label.is_empty() {
+                true => dot.push_str(&format!("    \"{from}\" -> \"{to}\";\n")),
+                false => dot.push_str(&format!(
+                    "    \"{from}\" -> \"{to}\" [label=\"{label}\"];\n"
+                )),
+            }
+        }
+        for (from, to) in traced {
+            dot.push_str(&format!("    \"{from}\" -> \"{to}\" [style=dashed];\n"));
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        collections::HashMap,
+        fmt::Display,
+        rc::Rc,
+        time::Duration,
+    };
+
+    use fsm_macros::{State, StateMachine};
+
+    use super::{
+        FiniteStateMachine, StateSignals, StateTimer, TransitionError,
+        regions::{OrthogonalRegion, Region, RegionGuards},
+        trace::{TransitionReason, TransitionTrace},
+    };
+    use crate::common::states::{State, StateBase};
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum TestState {
+        Idle,
+        Walk,
+        Menu,
+        Done,
+        Missing,
+    }
+
+    impl Display for TestState {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{self:?}")
+        }
+    }
+
+    /// Shared by the machine and its states, records their calls in order
+    #[derive(Debug, Default)]
+    struct TestContext {
+        log: RefCell<Vec<String>>,
+        pop_menu: Cell<bool>,
+        aim: Cell<bool>,
+        idle_frames: Cell<u32>,
+    }
+
+    impl TestContext {
+        fn log(&self, entry: impl Into<String>) {
+            self.log.borrow_mut().push(entry.into());
+        }
+    }
+
+    #[derive(Debug, State)]
+    #[state(name = TestState::Idle)]
+    struct Idle {
+        #[state(context)]
+        context: Rc<TestContext>,
+        #[state(next_state)]
+        next_state: Option<TestState>,
+    }
+
+    impl State<()> for Idle {
+        fn enter(&mut self) {
+            self.context.log("Idle::enter");
+        }
+
+        fn exit(&mut self) {
+            self.context.log("Idle::exit");
+            self.set_next_state(TestState::Idle);
+        }
+
+        fn paused(&mut self) {
+            self.context.log("Idle::paused");
+            self.set_next_state(TestState::Idle);
+        }
+
+        fn resumed(&mut self) {
+            self.context.log("Idle::resumed");
+        }
+
+        fn physics_process(&mut self, _delta: f32) {
+            let frames = self.context.idle_frames.get();
+            self.context.idle_frames.set(frames + 1);
+        }
+
+        fn transitions(&self) -> Vec<TestState> {
+            vec![TestState::Walk, TestState::Menu]
+        }
+    }
+
+    #[derive(Debug, State)]
+    #[state(name = TestState::Walk)]
+    struct Walk {
+        #[state(context)]
+        context: Rc<TestContext>,
+        #[state(next_state)]
+        next_state: Option<TestState>,
+    }
+
+    impl State<()> for Walk {
+        fn enter(&mut self) {
+            self.context.log("Walk::enter");
+        }
+
+        fn exit(&mut self) {
+            self.context.log("Walk::exit");
+            self.set_next_state(TestState::Walk);
+        }
+
+        fn min_dwell(&self) -> Duration {
+            Duration::from_millis(500)
+        }
+
+        fn after(&self) -> Option<(Duration, TestState)> {
+            Some((Duration::from_secs(1), TestState::Idle))
+        }
+
+        fn transitions(&self) -> Vec<TestState> {
+            vec![TestState::Done]
+        }
+    }
+
+    #[derive(Debug, State)]
+    #[state(name = TestState::Menu)]
+    struct Menu {
+        #[state(context)]
+        context: Rc<TestContext>,
+        #[state(next_state)]
+        next_state: Option<TestState>,
+    }
+
+    impl State<()> for Menu {
+        fn enter(&mut self) {
+            self.context.log("Menu::enter");
+        }
+
+        fn exit(&mut self) {
+            self.context.log("Menu::exit");
+            self.context.pop_menu.set(false);
+            self.set_next_state(TestState::Menu);
+        }
+
+        fn is_pushdown(&self) -> bool {
+            true
+        }
+
+        fn should_pop(&mut self) -> bool {
+            self.context.pop_menu.get()
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum ActionState {
+        Rest,
+        Aim,
+    }
+
+    impl Display for ActionState {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{self:?}")
+        }
+    }
+
+    #[derive(Debug, State)]
+    #[state(name = ActionState::Rest)]
+    struct Rest {
+        #[state(context)]
+        context: Rc<TestContext>,
+        #[state(next_state)]
+        next_state: Option<ActionState>,
+    }
+
+    impl State<()> for Rest {
+        fn physics_process(&mut self, _delta: f32) {
+            if self.context.aim.get() {
+                self.set_next_state(ActionState::Aim);
+            }
+        }
+    }
+
+    #[derive(Debug, State)]
+    #[state(name = ActionState::Aim)]
+    struct Aim {
+        #[state(context)]
+        context: Rc<TestContext>,
+        #[state(next_state)]
+        next_state: Option<ActionState>,
+    }
+
+    impl State<()> for Aim {
+        fn physics_process(&mut self, _delta: f32) {
+            if !self.context.aim.get() {
+                self.set_next_state(ActionState::Rest);
+            }
+        }
+    }
+
+    type TestStates =
+        HashMap<TestState, Box<dyn State<(), Context = Rc<TestContext>, StatesEnum = TestState>>>;
+
+    #[derive(Debug, StateMachine)]
+    struct TestMachine {
+        context: Rc<TestContext>,
+        states: TestStates,
+        current_state: TestState,
+        transitioning: bool,
+        state_stack: Vec<TestState>,
+        state_timer: StateTimer,
+        trace: TransitionTrace,
+        regions: Vec<Box<dyn OrthogonalRegion<(), Rc<TestContext>>>>,
+        guards: RegionGuards<TestState>,
+    }
+
+    impl TestMachine {
+        fn new() -> Self {
+            let mut machine = TestMachine {
+                context: Rc::default(),
+                states: HashMap::new(),
+                current_state: TestState::Idle,
+                transitioning: false,
+                state_stack: vec![],
+                state_timer: StateTimer::default(),
+                trace: TransitionTrace::with_capacity(8),
+                regions: vec![],
+                guards: RegionGuards::default(),
+            };
+            machine.states = machine.setup_states(machine.context.clone());
+
+            machine
+        }
+
+        fn request(&mut self, from: TestState, to: TestState) {
+            self.get_state(&from)
+                .expect("the state is registered")
+                .set_next_state(to);
+        }
+
+        fn take_log(&self) -> Vec<String> {
+            self.context.log.take()
+        }
+
+        /// Adds an "actions" region that aims while `TestContext::aim` is set
+        fn add_actions_region(&mut self, region: Region<(), Rc<TestContext>, ActionState>) {
+            let region = region.with_state_of::<Rest>().with_state_of::<Aim>();
+
+            self.regions.push(Box::new(region));
+            self.start_regions(self.context.clone());
+        }
+    }
+
+    impl StateSignals for TestMachine {
+        fn emit_state_entered(&mut self, name: &str) {
+            self.context.log(format!("entered {name}"));
+        }
+
+        fn emit_state_exited(&mut self, name: &str) {
+            self.context.log(format!("exited {name}"));
+        }
+
+        fn emit_transition(&mut self, from: &str, to: &str) {
+            self.context.log(format!("{from} -> {to}"));
+        }
+
+        fn emit_transition_error(&mut self, error: &str) {
+            self.context.log(format!("error {error}"));
+        }
+
+        fn emit_finished(&mut self, state: &str) {
+            self.context.log(format!("finished {state}"));
+        }
+    }
+
+    impl FiniteStateMachine for TestMachine {
+        type StatesEnum = TestState;
+        type Context = Rc<TestContext>;
+        type Input = ();
+
+        fn ready(&mut self) {}
+
+        fn setup_states(&mut self, context: Rc<TestContext>) -> TestStates {
+            let mut states = HashMap::new();
+            self.register_state(Box::new(Idle::new(context.clone())), &mut states);
+            self.register_state(Box::new(Walk::new(context.clone())), &mut states);
+            self.register_state(Box::new(Menu::new(context)), &mut states);
+
+            states
+        }
+
+        fn get_current_state(&self) -> TestState {
+            self.current_state.clone()
+        }
+
+        fn set_current_state(&mut self, state: TestState) {
+            self.current_state = state;
+        }
+
+        fn set_transitioning(&mut self, in_transition: bool) {
+            self.transitioning = in_transition;
+        }
+
+        fn get_transitioning(&self) -> bool {
+            self.transitioning
+        }
+
+        fn get_state_stack(&mut self) -> &mut Vec<TestState> {
+            &mut self.state_stack
+        }
+
+        fn get_state_timer(&mut self) -> &mut StateTimer {
+            &mut self.state_timer
+        }
+
+        fn get_states_map(&mut self) -> &mut TestStates {
+            &mut self.states
+        }
+
+        fn is_final_state(&self, state: &TestState) -> bool {
+            *state == TestState::Done
+        }
+
+        fn release_context(&mut self) {
+            let states = self.states.len();
+            self.context
+                .log(format!("release_context with {states} states"));
+        }
+
+        fn free_machine(&mut self) {
+            self.context.log("free_machine");
+        }
+
+        fn get_trace(&mut self) -> Option<&mut TransitionTrace> {
+            Some(&mut self.trace)
+        }
+
+        fn get_regions(
+            &mut self,
+        ) -> Option<&mut Vec<Box<dyn OrthogonalRegion<(), Rc<TestContext>>>>> {
+            Some(&mut self.regions)
+        }
+
+        fn can_transition(&mut self, _from: &TestState, to: &TestState) -> bool {
+            let snapshot = self.get_state_snapshot();
+            self.guards.allows(to, &snapshot)
+        }
+    }
+
+    /// Pushes Menu on top of Idle
+    fn open_menu(machine: &mut TestMachine) {
+        machine.request(TestState::Idle, TestState::Menu);
+        machine.physics_process(0.25);
+        machine.take_log();
+    }
+
+    #[test]
+    fn requested_transition_exits_and_enters() {
+        let mut machine = TestMachine::new();
+        machine.request(TestState::Idle, TestState::Walk);
+        machine.physics_process(0.25);
+
+        assert_eq!(machine.get_current_state(), TestState::Walk);
+        assert_eq!(
+            machine.take_log(),
+            [
+                "Idle::exit",
+                "exited Idle",
+                "Walk::enter",
+                "entered Walk",
+                "Idle -> Walk"
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_state_leaves_current_state_untouched() {
+        let mut machine = TestMachine::new();
+
+        let result = machine.transition_to_state(TestState::Missing);
+
+        assert!(matches!(
+            result,
+            Err(TransitionError::MissingState(TestState::Missing))
+        ));
+        assert_eq!(machine.get_current_state(), TestState::Idle);
+        assert!(!machine.get_transitioning());
+        assert!(machine.take_log().is_empty());
+
+        machine.request(TestState::Idle, TestState::Missing);
+        machine.physics_process(0.25);
+
+        assert_eq!(machine.get_current_state(), TestState::Idle);
+        assert!(!machine.get_transitioning());
+        assert_eq!(machine.take_log(), ["error Unable to get state: Missing"]);
+        assert_eq!(machine.context.idle_frames.get(), 0);
+
+        // The request was rolled back, Idle keeps running without retrying it
+        machine.physics_process(0.25);
+        machine.physics_process(0.25);
+
+        assert_eq!(machine.get_current_state(), TestState::Idle);
+        assert_eq!(machine.context.idle_frames.get(), 2);
+        assert!(machine.take_log().is_empty());
+        assert_eq!(
+            machine
+                .trace
+                .records()
+                .filter(|record| record.reason == TransitionReason::Failed)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn failed_transitions_clear_transitioning() {
+        let mut machine = TestMachine::new();
+
+        machine.set_current_state(TestState::Missing);
+        assert!(machine.push_state(TestState::Menu).is_err());
+        assert!(!machine.get_transitioning());
+        assert!(machine.get_state_stack().is_empty());
+
+        machine.set_current_state(TestState::Idle);
+        assert!(machine.push_state(TestState::Missing).is_err());
+        assert!(!machine.get_transitioning());
+        assert!(machine.get_state_stack().is_empty());
+
+        machine.get_state_stack().push(TestState::Missing);
+        assert!(matches!(
+            machine.pop_state(),
+            Err(TransitionError::MissingState(TestState::Missing))
+        ));
+        assert!(!machine.get_transitioning());
+        assert_eq!(machine.get_state_stack(), &[TestState::Missing]);
+        assert_eq!(machine.get_current_state(), TestState::Idle);
+        assert!(machine.take_log().is_empty());
+    }
+
+    #[test]
+    fn pushdown_state_suspends_and_resumes() {
+        let mut machine = TestMachine::new();
+        machine.request(TestState::Idle, TestState::Menu);
+        machine.physics_process(0.25);
+
+        assert_eq!(machine.get_current_state(), TestState::Menu);
+        assert_eq!(machine.get_state_stack(), &[TestState::Idle]);
+        assert_eq!(
+            machine.take_log(),
+            [
+                "Idle::paused",
+                "Menu::enter",
+                "entered Menu",
+                "Idle -> Menu"
+            ]
+        );
+
+        machine.context.pop_menu.set(true);
+        machine.physics_process(0.25);
+
+        assert_eq!(machine.get_current_state(), TestState::Idle);
+        assert!(machine.get_state_stack().is_empty());
+        assert_eq!(
+            machine.take_log(),
+            [
+                "Menu::exit",
+                "exited Menu",
+                "Idle::resumed",
+                "entered Idle",
+                "Menu -> Idle"
+            ]
+        );
+
+        assert!(matches!(
+            machine.pop_state(),
+            Err(TransitionError::EmptyStateStack)
+        ));
+    }
+
+    #[test]
+    fn regular_transition_unwinds_suspended_states() {
+        let mut machine = TestMachine::new();
+        open_menu(&mut machine);
+
+        machine.request(TestState::Menu, TestState::Walk);
+        machine.physics_process(0.25);
+
+        assert_eq!(machine.get_current_state(), TestState::Walk);
+        assert!(machine.get_state_stack().is_empty());
+        assert_eq!(
+            machine.take_log(),
+            [
+                "Menu::exit",
+                "exited Menu",
+                "Idle::exit",
+                "exited Idle",
+                "Walk::enter",
+                "entered Walk",
+                "Menu -> Walk"
+            ]
+        );
+    }
+
+    #[test]
+    fn pop_restores_the_elapsed_time_of_the_resumed_state() {
+        let mut machine = TestMachine::new();
+        machine.physics_process(0.25);
+        machine.physics_process(0.25);
+        open_menu(&mut machine);
+
+        assert_eq!(machine.get_state_elapsed(), Duration::ZERO);
+
+        machine.physics_process(0.25);
+        machine.context.pop_menu.set(true);
+        machine.physics_process(0.25);
+
+        assert_eq!(machine.get_current_state(), TestState::Idle);
+        assert_eq!(machine.get_state_elapsed(), Duration::from_millis(750));
+    }
+
+    #[test]
+    fn min_dwell_delays_requested_transition() {
+        let mut machine = TestMachine::new();
+        machine.request(TestState::Idle, TestState::Walk);
+        machine.physics_process(0.25);
+        machine.request(TestState::Walk, TestState::Idle);
+
+        machine.physics_process(0.25);
+        assert_eq!(machine.get_current_state(), TestState::Walk);
+
+        machine.physics_process(0.25);
+        assert_eq!(machine.get_current_state(), TestState::Idle);
+        let record = machine.trace.records().last().expect("a transition");
+        assert_eq!(record.reason, TransitionReason::Requested);
+    }
+
+    #[test]
+    fn after_times_out_into_the_next_state() {
+        let mut machine = TestMachine::new();
+        machine.request(TestState::Idle, TestState::Walk);
+        machine.physics_process(0.25);
+
+        for _ in 0..3 {
+            machine.physics_process(0.25);
+        }
+        assert_eq!(machine.get_current_state(), TestState::Walk);
+
+        machine.physics_process(0.25);
+        assert_eq!(machine.get_current_state(), TestState::Idle);
+        let record = machine.trace.records().last().expect("a transition");
+        assert_eq!(
+            (record.from.as_str(), record.to.as_str(), record.reason),
+            ("Walk", "Idle", TransitionReason::Timeout)
+        );
+    }
+
+    #[test]
+    fn trace_keeps_the_latest_transitions() {
+        let mut machine = TestMachine::new();
+        machine.trace.set_capacity(2);
+
+        machine.transition_to_state(TestState::Walk).unwrap();
+        machine.transition_to_state(TestState::Idle).unwrap();
+        let _ = machine.transition_to_state(TestState::Missing);
+
+        let records: Vec<_> = machine
+            .trace
+            .records()
+            .map(|record| (record.from.as_str(), record.to.as_str(), record.reason))
+            .collect();
+        assert_eq!(
+            records,
+            [
+                ("Walk", "Idle", TransitionReason::Requested),
+                ("Idle", "Missing", TransitionReason::Failed)
+            ]
+        );
+
+        machine.trace.set_capacity(1);
+        assert_eq!(machine.trace.records().count(), 1);
+
+        machine.trace.set_capacity(0);
+        machine.transition_to_state(TestState::Walk).unwrap();
+        assert!(!machine.trace.is_enabled());
+        assert_eq!(machine.trace.records().count(), 0);
+    }
+
+    #[test]
+    fn trace_to_json() {
+        let mut machine = TestMachine::new();
+        machine.request(TestState::Idle, TestState::Walk);
+        machine.physics_process(0.25);
+
+        let json = machine.trace.to_json().unwrap();
+
+        assert_eq!(
+            json,
+            r#"[
+  {
+    "frame": 1,
+    "from": "Idle",
+    "to": "Walk",
+    "reason": "requested"
+  }
+]"#
+        );
+    }
+
+    #[test]
+    fn to_dot_draws_declared_and_traced_transitions() {
+        let mut machine = TestMachine::new();
+        open_menu(&mut machine);
+        machine.request(TestState::Menu, TestState::Walk);
+        machine.physics_process(0.25);
+
+        assert_eq!(
+            machine.to_dot(),
+            r#"digraph TestMachine {
+    rankdir=LR;
+    "Done" [shape=doublecircle, style=solid];
+    "Idle" [shape=ellipse, style=solid];
+    "Menu" [shape=box, style=solid];
+    "Walk" [shape=ellipse, style=bold];
+    "Idle" -> "Menu";
+    "Idle" -> "Walk";
+    "Walk" -> "Done";
+    "Walk" -> "Idle" [label="after 1.00s"];
+    "Menu" -> "Walk" [style=dashed];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn finish_tears_the_machine_down_in_order() {
+        let mut machine = TestMachine::new();
+        open_menu(&mut machine);
+
+        machine.request(TestState::Menu, TestState::Done);
+        machine.physics_process(0.25);
+
+        assert!(machine.is_finished());
+        assert!(machine.get_states_map().is_empty());
+        assert!(machine.get_state_stack().is_empty());
+        assert_eq!(
+            machine.take_log(),
+            [
+                "Menu::exit",
+                "exited Menu",
+                "Idle::exit",
+                "exited Idle",
+                "release_context with 0 states",
+                "Menu -> Done",
+                "finished Done",
+                "free_machine"
+            ]
+        );
+
+        machine.physics_process(0.25);
+        machine.input(());
+        assert!(machine.take_log().is_empty());
+    }
+
+    #[test]
+    fn snapshot_combines_the_regions() {
+        let mut machine = TestMachine::new();
+        machine.add_actions_region(Region::new("actions", ActionState::Rest));
+
+        machine.context.aim.set(true);
+        machine.physics_process(0.25);
+        machine.physics_process(0.25);
+
+        let snapshot = machine.get_state_snapshot();
+        assert_eq!(snapshot.to_string(), "main: Idle, actions: Aim");
+        assert!(snapshot.is_in("main", TestState::Idle));
+        assert!(snapshot.is_in("actions", ActionState::Aim));
+        assert_eq!(
+            machine.take_log(),
+            [
+                "exited actions/Rest",
+                "entered actions/Aim",
+                "actions/Rest -> actions/Aim"
+            ]
+        );
+    }
+
+    #[test]
+    fn machine_guard_blocks_transitions_while_a_region_is_in_a_state() {
+        let mut machine = TestMachine::new();
+        machine.add_actions_region(Region::new("actions", ActionState::Rest));
+        machine.guards.push(|to, snapshot| {
+            !(*to == TestState::Walk && snapshot.is_in("actions", ActionState::Aim))
+        });
+
+        machine.context.aim.set(true);
+        machine.physics_process(0.25);
+        machine.physics_process(0.25);
+        machine.request(TestState::Idle, TestState::Walk);
+        machine.physics_process(0.25);
+
+        assert_eq!(machine.get_current_state(), TestState::Idle);
+
+        machine.context.aim.set(false);
+        machine.physics_process(0.25);
+        machine.physics_process(0.25);
+
+        assert_eq!(machine.get_current_state(), TestState::Walk);
+    }
+
+    #[test]
+    fn region_guard_blocks_transitions_while_the_machine_is_in_a_state() {
+        let mut machine = TestMachine::new();
+        let region = Region::new("actions", ActionState::Rest).with_guard(|to, snapshot| {
+            !(*to == ActionState::Aim && snapshot.is_in("main", TestState::Menu))
+        });
+        machine.add_actions_region(region);
+        open_menu(&mut machine);
+
+        machine.context.aim.set(true);
+        machine.physics_process(0.25);
+        machine.physics_process(0.25);
+
+        assert!(
+            machine
+                .get_state_snapshot()
+                .is_in("actions", ActionState::Rest)
+        );
+
+        machine.context.pop_menu.set(true);
+        machine.physics_process(0.25);
+        machine.physics_process(0.25);
+
+        assert!(
+            machine
+                .get_state_snapshot()
+                .is_in("actions", ActionState::Aim)
+        );
     }
 }
@@ -0,0 +1,161 @@
+//! Thin Godot layer on top of the engine independent state machine core
+
+use godot::{classes::InputEvent, global::godot_error, obj::Gd};
+
+use crate::common::states::StateError;
+
+/// Input type used by every state machine that runs as a Godot node
+pub type GodotInput = Gd<InputEvent>;
+
+/// State error hook that logs to the Godot output, set when the extension
+/// is loaded
+pub fn log_state_error(error: &StateError) {
+    godot_error!("{error}");
+}
+
+#[macro_export]
+macro_rules! impl_inode3d_for_fsm {
+    ($machine: ident) => {
+        #[godot_api]
+        impl godot::classes::INode3D for $machine {
+            fn ready(&mut self) {
+                $crate::common::finite_state_machine::FiniteStateMachine::ready(self);
+            }
+
+            fn input(&mut self, event: godot::obj::Gd<godot::classes::InputEvent>) {
+                $crate::common::finite_state_machine::FiniteStateMachine::input(self, event);
+            }
+
+            fn process(&mut self, delta: f64) {
+                $crate::common::finite_state_machine::FiniteStateMachine::process(self, delta);
+            }
+
+            fn physics_process(&mut self, delta: f64) {
+                $crate::common::finite_state_machine::FiniteStateMachine::physics_process(
+                    self, delta,
+                );
+            }
+
+            fn exit_tree(&mut self) {
+                $crate::common::finite_state_machine::FiniteStateMachine::exit_tree(self);
+            }
+        }
+
+        #[godot_api]
+        impl $machine {
+            #[signal]
+            /// The machine entered the state with the given name
+            pub fn state_entered(name: godot::builtin::GString);
+
+            #[signal]
+            /// The machine exited the state with the given name
+            pub fn state_exited(name: godot::builtin::GString);
+
+            #[signal]
+            /// The machine moved from one state to another
+            pub fn transition(from: godot::builtin::GString, to: godot::builtin::GString);
+
+            #[signal]
+            /// A transition failed and the machine was rolled back
+            pub fn transition_failed(error: godot::builtin::GString);
+
+            #[signal]
+            /// The machine reached a final state and was torn down
+            pub fn finished(state: godot::builtin::GString);
+
+            #[func]
+            /// Returns the name of the state the machine is currently in
+            pub fn get_current_state_name(&self) -> godot::builtin::GString {
+                $crate::common::finite_state_machine::FiniteStateMachine::get_current_state(self)
+                    .to_string()
+                    .into()
+            }
+
+            #[func]
+            /// Current state of every region, e.g. "main: Idle, actions: Aim"
+            pub fn get_state_snapshot(&mut self) -> godot::builtin::GString {
+                $crate::common::finite_state_machine::FiniteStateMachine::get_state_snapshot(self)
+                    .to_string()
+                    .into()
+            }
+
+            #[func]
+            /// Records the last `capacity` transitions, 0 stops recording
+            pub fn set_trace_capacity(&mut self, capacity: i64) {
+                if let Some(trace) =
+                    $crate::common::finite_state_machine::FiniteStateMachine::get_trace(self)
+                {
+                    trace.set_capacity(capacity.max(0) as usize);
+                }
+            }
+
+            #[func]
+            /// Returns the recorded transitions as a JSON array
+            pub fn dump_trace(&mut self) -> godot::builtin::GString {
+                let Some(trace) =
+                    $crate::common::finite_state_machine::FiniteStateMachine::get_trace(self)
+                else {
+                    return "[]".into();
+                };
+
+                match trace.to_json() {
+                    Ok(json) => json.into(),
+                    Err(error) => {
+                        godot::global::godot_error!("{}:: {error}", stringify!($machine));
+                        "[]".into()
+                    }
+                }
+            }
+
+            #[func]
+            /// Returns the state graph of the machine in Graphviz DOT format
+            pub fn export_state_graph(&mut self) -> godot::builtin::GString {
+                $crate::common::finite_state_machine::FiniteStateMachine::to_dot(self).into()
+            }
+        }
+
+        impl $crate::common::finite_state_machine::StateSignals for $machine {
+            fn emit_state_entered(&mut self, name: &str) {
+                use godot::obj::WithUserSignals;
+
+                self.signals()
+                    .state_entered()
+                    .emit(&godot::builtin::GString::from(name));
+            }
+
+            fn emit_state_exited(&mut self, name: &str) {
+                use godot::obj::WithUserSignals;
+
+                self.signals()
+                    .state_exited()
+                    .emit(&godot::builtin::GString::from(name));
+            }
+
+            fn emit_transition(&mut self, from: &str, to: &str) {
+                use godot::obj::WithUserSignals;
+
+                self.signals().transition().emit(
+                    &godot::builtin::GString::from(from),
+                    &godot::builtin::GString::from(to),
+                );
+            }
+
+            fn emit_transition_error(&mut self, error: &str) {
+                use godot::obj::WithUserSignals;
+
+                godot::global::godot_error!("{}:: {error}", stringify!($machine));
+                self.signals()
+                    .transition_failed()
+                    .emit(&godot::builtin::GString::from(error));
+            }
+
+            fn emit_finished(&mut self, state: &str) {
+                use godot::obj::WithUserSignals;
+
+                self.signals()
+                    .finished()
+                    .emit(&godot::builtin::GString::from(state));
+            }
+        }
+    };
+}
@@ -0,0 +1,313 @@
+//! Orthogonal regions, extra state machines that run next to the main
+//! states of a `FiniteStateMachine` and share its context
+
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+    hash::Hash,
+};
+
+use crate::common::states::{State, StateBase};
+
+use super::{FiniteStateMachine, StateSignals, StateTimer};
+
+/// The current state of every region of a machine, keyed by region name
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StateSnapshot {
+    regions: Vec<(String, String)>,
+}
+
+impl StateSnapshot {
+    pub fn insert(&mut self, region: &str, state: String) {
+        self.regions.push((region.to_string(), state));
+    }
+
+    pub fn get(&self, region: &str) -> Option<&str> {
+        self.regions
+            .iter()
+            .find(|(name, _)| name == region)
+            .map(|(_, state)| state.as_str())
+    }
+
+    pub fn is_in(&self, region: &str, state: impl Display) -> bool {
+        self.get(region) == Some(state.to_string().as_str())
+    }
+}
+
+impl Display for StateSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let regions: Vec<String> = self
+            .regions
+            .iter()
+            .map(|(region, state)| format!("{region}: {state}"))
+            .collect();
+
+        write!(f, "{}", regions.join(", "))
+    }
+}
+
+/// State changes of a region, forwarded by the owning machine
+#[derive(Debug)]
+pub enum RegionEvent {
+    Entered(String),
+    Exited(String),
+    Transition(String, String),
+    TransitionError(String),
+}
+
+/// Refuses a transition of a region into the given state, based on the
+/// states the other regions of the machine are in
+pub type RegionGuard<S> = Box<dyn Fn(&S, &StateSnapshot) -> bool>;
+
+/// Cross region guards of a machine or region, every guard has to allow
+/// the transition
+pub struct RegionGuards<S> {
+    guards: Vec<RegionGuard<S>>,
+}
+
+impl<S> Default for RegionGuards<S> {
+    fn default() -> Self {
+        RegionGuards { guards: vec![] }
+    }
+}
+
+impl<S> Debug for RegionGuards<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RegionGuards({})", self.guards.len())
+    }
+}
+
+impl<S> RegionGuards<S> {
+    pub fn push(&mut self, guard: impl Fn(&S, &StateSnapshot) -> bool + 'static) {
+        self.guards.push(Box::new(guard));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.guards.is_empty()
+    }
+
+    pub fn allows(&self, to: &S, snapshot: &StateSnapshot) -> bool {
+        self.guards.iter().all(|guard| guard(to, snapshot))
+    }
+}
+
+type RegionState<I, C, S> = Box<dyn State<I, Context = C, StatesEnum = S>>;
+type StateConstructor<I, C, S> = fn(C) -> RegionState<I, C, S>;
+
+pub struct Region<I, C, S> {
+    name: &'static str,
+    /// Context shared with the owning machine, handed over when the
+    /// machine starts its regions
+    context: Option<C>,
+    state_constructors: Vec<StateConstructor<I, C, S>>,
+    states: HashMap<S, RegionState<I, C, S>>,
+    current_state: S,
+    transitioning: bool,
+    state_timer: StateTimer,
+    state_stack: Vec<S>,
+    guards: RegionGuards<S>,
+    snapshot: StateSnapshot,
+    events: Vec<RegionEvent>,
+}
+
+impl<I, C, S> Region<I, C, S>
+where
+    S: PartialEq + Eq + Hash + Clone + Debug + Display,
+{
+    pub fn new(name: &'static str, initial_state: S) -> Self {
+        Region {
+            name,
+            context: None,
+            state_constructors: vec![],
+            states: HashMap::new(),
+            current_state: initial_state,
+            transitioning: false,
+            state_timer: StateTimer::default(),
+            state_stack: vec![],
+            guards: RegionGuards::default(),
+            snapshot: StateSnapshot::default(),
+            events: vec![],
+        }
+    }
+
+    /// Adds a state created from the machine's context once the region
+    /// starts, the same way the machine creates its own states
+    pub fn with_state_of<T>(mut self) -> Self
+    where
+        T: State<I, Context = C, StatesEnum = S> + 'static,
+    {
+        self.state_constructors
+            .push(|context| Box::new(T::new(context)));
+
+        self
+    }
+
+    pub fn with_state(mut self, state: RegionState<I, C, S>) -> Self {
+        let state_name = state.get_state_name();
+        self.states.insert(state_name, state);
+
+        self
+    }
+
+    pub fn with_guard(mut self, guard: impl Fn(&S, &StateSnapshot) -> bool + 'static) -> Self {
+        self.guards.push(guard);
+
+        self
+    }
+}
+
+impl<I, C, S: Debug> Debug for Region<I, C, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Region")
+            .field("name", &self.name)
+            .field("current_state", &self.current_state)
+            .field("state_stack", &self.state_stack)
+            .finish()
+    }
+}
+
+impl<I, C, S> StateSignals for Region<I, C, S> {
+    fn emit_state_entered(&mut self, name: &str) {
+        self.events.push(RegionEvent::Entered(name.to_string()));
+    }
+
+    fn emit_state_exited(&mut self, name: &str) {
+        self.events.push(RegionEvent::Exited(name.to_string()));
+    }
+
+    fn emit_transition(&mut self, from: &str, to: &str) {
+        self.events
+            .push(RegionEvent::Transition(from.to_string(), to.to_string()));
+    }
+
+    fn emit_transition_error(&mut self, error: &str) {
+        self.events
+            .push(RegionEvent::TransitionError(error.to_string()));
+    }
+
+    // NOTE: A finished region stays in its final state while the
+    // owning machine keeps running
+    fn emit_finished(&mut self, _state: &str) {}
+}
+
+impl<I, C, S> FiniteStateMachine for Region<I, C, S>
+where
+    I: Clone,
+    C: Clone,
+    S: PartialEq + Eq + Hash + Clone + Debug + Display,
+{
+    type StatesEnum = S;
+    type Context = C;
+    type Input = I;
+
+    fn ready(&mut self) {
+        let state = self.current_state.clone();
+        if let Some(current_state) = self.get_state(&state) {
+            current_state.enter();
+        }
+    }
+
+    fn setup_states(&mut self, context: Self::Context) -> HashMap<S, RegionState<I, C, S>> {
+        self.state_constructors
+            .iter()
+            .map(|constructor| {
+                let state = constructor(context.clone());
+                (state.get_state_name(), state)
+            })
+            .collect()
+    }
+
+    fn get_current_state(&self) -> Self::StatesEnum {
+        self.current_state.clone()
+    }
+
+    fn set_current_state(&mut self, state: Self::StatesEnum) {
+        self.current_state = state;
+    }
+
+    fn set_transitioning(&mut self, in_transition: bool) {
+        self.transitioning = in_transition;
+    }
+
+    fn get_transitioning(&self) -> bool {
+        self.transitioning
+    }
+
+    fn get_state_stack(&mut self) -> &mut Vec<Self::StatesEnum> {
+        &mut self.state_stack
+    }
+
+    fn get_state_timer(&mut self) -> &mut StateTimer {
+        &mut self.state_timer
+    }
+
+    fn get_states_map(&mut self) -> &mut HashMap<S, RegionState<I, C, S>> {
+        &mut self.states
+    }
+
+    fn release_context(&mut self) {
+        self.context = None;
+    }
+
+    fn can_transition(&mut self, _from: &Self::StatesEnum, to: &Self::StatesEnum) -> bool {
+        self.guards.allows(to, &self.snapshot)
+    }
+}
+
+/// Type erased region so machines can own regions with different state enums
+pub trait OrthogonalRegion<Input, Context>: Debug {
+    fn name(&self) -> &str;
+    fn current_state_name(&self) -> String;
+    /// Creates the region's states from the machine's context and enters
+    /// the initial state
+    fn start(&mut self, context: Context);
+    fn update_snapshot(&mut self, snapshot: StateSnapshot);
+    fn input(&mut self, event: Input);
+    fn process(&mut self, delta: f64);
+    fn physics_process(&mut self, delta: f64);
+    fn drain_events(&mut self) -> Vec<RegionEvent>;
+}
+
+impl<I, C, S> OrthogonalRegion<I, C> for Region<I, C, S>
+where
+    I: Clone,
+    C: Clone,
+    S: PartialEq + Eq + Hash + Clone + Debug + Display,
+{
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn current_state_name(&self) -> String {
+        self.current_state.to_string()
+    }
+
+    fn start(&mut self, context: C) {
+        self.context = Some(context.clone());
+
+        let states = self.setup_states(context);
+        self.states.extend(states);
+
+        FiniteStateMachine::ready(self);
+    }
+
+    fn update_snapshot(&mut self, snapshot: StateSnapshot) {
+        self.snapshot = snapshot;
+    }
+
+    fn input(&mut self, event: I) {
+        FiniteStateMachine::input(self, event);
+    }
+
+    fn process(&mut self, delta: f64) {
+        FiniteStateMachine::process(self, delta);
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        FiniteStateMachine::physics_process(self, delta);
+    }
+
+    fn drain_events(&mut self) -> Vec<RegionEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
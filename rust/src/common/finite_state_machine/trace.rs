@@ -0,0 +1,95 @@
+//! Transition history of a machine, kept in a ring buffer so it can stay
+//! enabled during play and be dumped when a machine gets stuck
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+/// Why a machine changed state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionReason {
+    /// The current state requested the next state
+    Requested,
+    /// The `after()` timeout of the current state ran out
+    Timeout,
+    /// A pushdown state was entered on top of the current state
+    Push,
+    /// A pushdown state was exited and the suspended state resumed
+    Pop,
+    /// `to` could not be entered and the machine stayed in `from`
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceRecord {
+    pub frame: u64,
+    pub from: String,
+    pub to: String,
+    pub reason: TransitionReason,
+}
+
+/// Keeps the last `capacity` transitions, a capacity of 0 disables recording
+#[derive(Debug, Default)]
+pub struct TransitionTrace {
+    capacity: usize,
+    frame: u64,
+    records: VecDeque<TraceRecord>,
+}
+
+impl TransitionTrace {
+    pub fn with_capacity(capacity: usize) -> Self {
+        TransitionTrace {
+            capacity,
+            frame: 0,
+            records: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+
+        while self.records.len() > capacity {
+            self.records.pop_front();
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Counts the updates of the machine, recorded as the frame of a transition
+    pub fn tick(&mut self) {
+        self.frame += 1;
+    }
+
+    pub fn record(&mut self, from: String, to: String, reason: TransitionReason) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+
+        self.records.push_back(TraceRecord {
+            frame: self.frame,
+            from,
+            to,
+            reason,
+        });
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &TraceRecord> {
+        self.records.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    /// Oldest transition first
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&self.records)
+    }
+}
@@ -0,0 +1,163 @@
+use godot::{
+    builtin::{Array, GString, PackedInt32Array, PackedStringArray, Vector3},
+    classes::{AudioStream, Node, Object, PhysicsMaterial, Resource},
+    meta::ToGodot,
+    obj::{Base, Gd},
+    prelude::GodotClass,
+};
+use rand::Rng;
+
+/// Terrain3D texture blend above which the overlay texture counts as the
+/// texture at a point
+const TERRAIN_OVERLAY_BLEND: f32 = 0.5;
+
+#[derive(GodotClass, Debug)]
+#[class(base=Resource, init)]
+/// Footstep sounds of one kind of floor surface, e.g. grass or stone.
+pub struct FootstepSoundSet {
+    base: Base<Resource>,
+
+    #[export]
+    /// Name of the surface, only used for debugging
+    pub surface: GString,
+
+    #[export]
+    /// One of these is picked at random for every footstep
+    pub streams: Array<Gd<AudioStream>>,
+
+    #[export]
+    /// Floors in one of these node groups use this set
+    pub groups: PackedStringArray,
+
+    #[export]
+    /// Floors with one of these physics materials use this set
+    pub physics_materials: Array<Gd<PhysicsMaterial>>,
+
+    #[export]
+    /// Terrain3D texture ids that use this set
+    pub terrain_textures: PackedInt32Array,
+
+    #[export(range=(-40.0, 20.0))]
+    /// Added to the volume the footstep player has in the scene
+    pub volume_db: f32,
+}
+
+impl FootstepSoundSet {
+    pub fn random_stream(&self) -> Option<Gd<AudioStream>> {
+        if self.streams.is_empty() {
+            return None;
+        }
+
+        let index = rand::rng().random_range(0..self.streams.len());
+        self.streams.get(index)
+    }
+
+    fn matches(&self, floor: &Gd<Object>, terrain_texture: Option<i32>) -> bool {
+        if let Some(texture) = terrain_texture {
+            return self.terrain_textures.as_slice().contains(&texture);
+        }
+
+        if let Ok(node) = floor.clone().try_cast::<Node>() {
+            let in_group = self
+                .groups
+                .as_slice()
+                .iter()
+                .any(|group| node.is_in_group(group.arg()));
+
+            if in_group {
+                return true;
+            }
+        }
+
+        // NOTE: Only static and rigid bodies have a physics material
+        let physics_material = floor
+            .get("physics_material_override")
+            .try_to::<Gd<PhysicsMaterial>>();
+
+        physics_material.is_ok_and(|material| {
+            self.physics_materials
+                .iter_shared()
+                .any(|candidate| candidate == material)
+        })
+    }
+}
+
+#[derive(GodotClass, Debug)]
+#[class(base=Resource, init)]
+/// Maps the floor under the player to a footstep sound set, the floor is
+/// matched by Terrain3D texture, node group or physics material.
+pub struct FootstepTable {
+    base: Base<Resource>,
+
+    #[export]
+    pub sound_sets: Array<Gd<FootstepSoundSet>>,
+
+    #[export]
+    /// Used when no sound set matches the floor
+    pub default_set: Option<Gd<FootstepSoundSet>>,
+
+    #[export]
+    /// Used while the feet are in a WaterVolume
+    pub water_set: Option<Gd<FootstepSoundSet>>,
+
+    #[export(range=(0.0, 1.0))]
+    /// Volume scale of the slowest footsteps, relative to walking speed
+    pub min_volume_scale: f32,
+
+    #[export(range=(1.0, 4.0))]
+    /// Volume scale of the fastest footsteps, relative to walking speed
+    pub max_volume_scale: f32,
+}
+
+impl FootstepTable {
+    /// Sound set for the floor object the player stands on at `position`
+    pub fn sound_set_for(
+        &self,
+        floor: &Gd<Object>,
+        position: Vector3,
+    ) -> Option<Gd<FootstepSoundSet>> {
+        let terrain_texture = terrain_texture_at(floor, position);
+
+        self.sound_sets
+            .iter_shared()
+            .find(|sound_set| sound_set.bind().matches(floor, terrain_texture))
+            .or_else(|| self.default_set.clone())
+    }
+
+    /// Volume scale for footsteps at `speed`, walking speed is 1
+    pub fn volume_scale(&self, speed: f32, walking_speed: f32) -> f32 {
+        (speed / walking_speed.max(f32::EPSILON))
+            .clamp(self.min_volume_scale, self.max_volume_scale)
+    }
+}
+
+/// Texture id of a Terrain3D floor at `position`, None for other floors
+fn terrain_texture_at(floor: &Gd<Object>, position: Vector3) -> Option<i32> {
+    if floor.get_class() != "Terrain3D" {
+        return None;
+    }
+
+    // NOTE: Terrain3D moved the texture lookup from its storage to its
+    // data in 1.0
+    let mut floor = floor.clone();
+    let accessor = if floor.has_method("get_data") {
+        "get_data"
+    } else {
+        "get_storage"
+    };
+
+    let mut data: Gd<Object> = floor.call(accessor, &[]).try_to().ok()?;
+    let texture: Vector3 = data
+        .call("get_texture_id", &[position.to_variant()])
+        .try_to()
+        .ok()?;
+
+    // x is the base texture, y the overlay texture and z their blend
+    let id = if texture.z > TERRAIN_OVERLAY_BLEND {
+        texture.y
+    } else {
+        texture.x
+    };
+
+    Some(id as i32)
+}
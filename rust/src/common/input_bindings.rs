@@ -0,0 +1,248 @@
+use godot::{
+    builtin::{Array, GString, StringName, VariantArray},
+    classes::{ConfigFile, FileAccess, INode, InputEvent, InputMap, Node},
+    global::{Error as GodotError, godot_error},
+    meta::ToGodot,
+    obj::{Base, Gd, NewGd, WithUserSignals},
+    prelude::{GodotClass, godot_api},
+};
+use thiserror::Error;
+
+use crate::actions::ACTIONS;
+
+/// Used when no config path is set on the node
+const DEFAULT_CONFIG_PATH: &str = "user://input_bindings.cfg";
+
+const BINDINGS_SECTION: &str = "bindings";
+
+#[derive(Error, Debug)]
+pub enum InputBindingsError {
+    #[error("{0} is not an action of the game")]
+    UnknownAction(String),
+    #[error("Only keys, mouse buttons and joypad buttons or axes can be bound")]
+    UnsupportedEvent,
+    #[error("The event is already bound to {0}")]
+    Conflict(&'static str),
+    #[error("The bindings could not be saved: {0:?}")]
+    Save(GodotError),
+    #[error("The bindings could not be loaded: {0:?}")]
+    Load(GodotError),
+}
+
+/// Rebinding an action only replaces the events of the same device, so a
+/// new key keeps the gamepad binding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputDevice {
+    KeyboardMouse,
+    Joypad,
+}
+
+impl InputDevice {
+    fn of(event: &Gd<InputEvent>) -> Option<Self> {
+        match event.get_class().to_string().as_str() {
+            "InputEventKey" | "InputEventMouseButton" => Some(Self::KeyboardMouse),
+            "InputEventJoypadButton" | "InputEventJoypadMotion" => Some(Self::Joypad),
+            _ => None,
+        }
+    }
+}
+
+#[derive(GodotClass, Debug)]
+#[class(base=Node, init)]
+/// InputBindings owns the bindings of the game actions, it rebinds them in
+/// the InputMap at runtime and saves them to a config file. It runs as the
+/// `Keymap` autoload so the bindings are loaded before any scene.
+pub struct InputBindings {
+    base: Base<Node>,
+
+    #[export]
+    /// Where the bindings are saved, user://input_bindings.cfg if empty
+    pub config_path: GString,
+}
+
+#[godot_api]
+impl INode for InputBindings {
+    fn ready(&mut self) {
+        if let Err(error) = self.load() {
+            godot_error!("{error}");
+        }
+    }
+}
+
+#[godot_api]
+impl InputBindings {
+    #[signal]
+    /// The events bound to an action changed
+    pub fn bindings_changed(action: StringName);
+
+    #[signal]
+    /// A rebind was refused because the event is bound to another action
+    pub fn binding_conflict(action: StringName, conflicting_action: StringName);
+
+    #[func]
+    /// Binds `event` to `action` in place of its events of the same device,
+    /// returns false if the event is bound to another action
+    pub fn rebind_action(&mut self, action: StringName, event: Gd<InputEvent>) -> bool {
+        self.rebind(&action.to_string(), event)
+            .map_err(|error| godot_error!("{error}"))
+            .is_ok()
+    }
+
+    #[func]
+    /// Actions other than `action` that `event` is already bound to
+    pub fn get_conflicts(&self, action: StringName, event: Gd<InputEvent>) -> Array<StringName> {
+        self.conflicts(&action.to_string(), &event)
+            .into_iter()
+            .map(StringName::from)
+            .collect()
+    }
+
+    #[func]
+    /// Restores the bindings of the project settings and saves them
+    pub fn reset_bindings(&mut self) {
+        InputMap::singleton().load_from_project_settings();
+
+        for action in ACTIONS.all() {
+            self.signals()
+                .bindings_changed()
+                .emit(&StringName::from(action));
+        }
+
+        if let Err(error) = self.save() {
+            godot_error!("{error}");
+        }
+    }
+
+    #[func]
+    pub fn save_bindings(&self) -> bool {
+        self.save().map_err(|error| godot_error!("{error}")).is_ok()
+    }
+
+    #[func]
+    pub fn load_bindings(&mut self) -> bool {
+        self.load().map_err(|error| godot_error!("{error}")).is_ok()
+    }
+}
+
+impl InputBindings {
+    pub fn rebind(
+        &mut self,
+        action: &str,
+        event: Gd<InputEvent>,
+    ) -> Result<(), InputBindingsError> {
+        let action = known_action(action)?;
+        let device = InputDevice::of(&event).ok_or(InputBindingsError::UnsupportedEvent)?;
+
+        if let Some(conflict) = self.conflicts(action, &event).first().copied() {
+            self.signals()
+                .binding_conflict()
+                .emit(&StringName::from(action), &StringName::from(conflict));
+
+            return Err(InputBindingsError::Conflict(conflict));
+        }
+
+        let mut input_map = InputMap::singleton();
+        for existing in input_map.action_get_events(action).iter_shared() {
+            if InputDevice::of(&existing) == Some(device) {
+                input_map.action_erase_event(action, &existing);
+            }
+        }
+
+        input_map.action_add_event(action, &event);
+        self.signals()
+            .bindings_changed()
+            .emit(&StringName::from(action));
+
+        self.save()
+    }
+
+    pub fn conflicts(&self, action: &str, event: &Gd<InputEvent>) -> Vec<&'static str> {
+        let input_map = InputMap::singleton();
+
+        ACTIONS
+            .all()
+            .into_iter()
+            .filter(|other| *other != action)
+            .filter(|other| input_map.action_has_event(*other, event))
+            .collect()
+    }
+
+    pub fn save(&self) -> Result<(), InputBindingsError> {
+        let input_map = InputMap::singleton();
+        let mut config = ConfigFile::new_gd();
+
+        for action in ACTIONS.all() {
+            let events: VariantArray = input_map
+                .action_get_events(action)
+                .iter_shared()
+                .map(|event| event.to_variant())
+                .collect();
+
+            config.set_value(BINDINGS_SECTION, action, &events.to_variant());
+        }
+
+        match config.save(&self.path()) {
+            GodotError::OK => Ok(()),
+            error => Err(InputBindingsError::Save(error)),
+        }
+    }
+
+    /// Replaces the bindings of every action saved in the config file, keeps
+    /// the project bindings if nothing was saved yet
+    pub fn load(&mut self) -> Result<(), InputBindingsError> {
+        let path = self.path();
+        if !FileAccess::file_exists(&path) {
+            return Ok(());
+        }
+
+        let mut config = ConfigFile::new_gd();
+        match config.load(&path) {
+            GodotError::OK => {}
+            error => return Err(InputBindingsError::Load(error)),
+        }
+
+        let mut input_map = InputMap::singleton();
+        for action in ACTIONS.all() {
+            if !config.has_section_key(BINDINGS_SECTION, action) {
+                continue;
+            }
+
+            let Ok(events) = config
+                .get_value(BINDINGS_SECTION, action)
+                .try_to::<VariantArray>()
+            else {
+                continue;
+            };
+
+            input_map.action_erase_events(action);
+            for event in events
+                .iter_shared()
+                .filter_map(|event| event.try_to::<Gd<InputEvent>>().ok())
+            {
+                input_map.action_add_event(action, &event);
+            }
+
+            self.signals()
+                .bindings_changed()
+                .emit(&StringName::from(action));
+        }
+
+        Ok(())
+    }
+
+    fn path(&self) -> GString {
+        if self.config_path.is_empty() {
+            GString::from(DEFAULT_CONFIG_PATH)
+        } else {
+            self.config_path.clone()
+        }
+    }
+}
+
+fn known_action(action: &str) -> Result<&'static str, InputBindingsError> {
+    ACTIONS
+        .all()
+        .into_iter()
+        .find(|known| *known == action)
+        .ok_or_else(|| InputBindingsError::UnknownAction(action.to_string()))
+}
@@ -1,14 +1,10 @@
-use std::sync::LazyLock;
-
 use godot::{
     classes::{INode3D, Input, InputEvent, input::MouseMode},
     obj::Gd,
     prelude::{GodotClass, godot_api},
 };
 
-use crate::actions::Actions;
-
-static ACTIONS: LazyLock<Actions> = LazyLock::new(Actions::default);
+use crate::actions::ACTIONS;
 
 #[derive(GodotClass)]
 #[class(base=Node3D, init)]
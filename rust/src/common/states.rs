@@ -1,12 +1,41 @@
 pub mod lootable;
 
-use godot::{classes::InputEvent, obj::Gd};
+use std::{sync::OnceLock, time::Duration};
 
-pub trait State: std::fmt::Debug {
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StateError {
+    #[error("The next_state could not be borrowed")]
+    NextState,
+    #[error("The active flag could not be borrowed")]
+    ActiveFlag,
+}
+
+/// Receives the borrow errors of the accessors `#[derive(State)]` generates
+/// for shared fields, set by the engine adapter
+static STATE_ERROR_HOOK: OnceLock<fn(&StateError)> = OnceLock::new();
+
+/// Sets where state errors are reported, only the first hook is kept
+pub fn set_state_error_hook(hook: fn(&StateError)) {
+    let _ = STATE_ERROR_HOOK.set(hook);
+}
+
+/// Reports an error of a generated accessor, printed to stderr until a hook
+/// is set
+pub fn report_state_error(error: StateError) {
+    match STATE_ERROR_HOOK.get() {
+        Some(hook) => hook(&error),
+        None => eprintln!("{error}"),
+    }
+}
+
+/// State bookkeeping that is usually generated with `#[derive(State)]`
+/// from the `fsm_macros` crate
+pub trait StateBase {
     type StatesEnum;
     type Context;
 
-    // TODO: Fix the second argument to a generic type, not a concrete type
     fn new(context: Self::Context) -> Self
     where
         Self: Sized;
@@ -15,13 +44,51 @@ pub trait State: std::fmt::Debug {
     fn set_next_state(&mut self, state: Self::StatesEnum);
     fn get_next_state(&mut self) -> Option<Self::StatesEnum>;
 
+    fn set_active(&mut self, _is_active: bool) {}
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn destroy(&mut self) {}
+}
+
+/// A state of a `FiniteStateMachine`, generic over the input event type
+/// so it does not depend on the engine
+pub trait State<Input>: StateBase + std::fmt::Debug {
     fn enter(&mut self) {}
     fn exit(&mut self) {}
 
-    // Godot methods
-    fn input(&mut self, _event: Gd<InputEvent>) {}
+    // Pushdown methods
+    /// Entering this state suspends the current one instead of exiting it
+    fn is_pushdown(&self) -> bool {
+        false
+    }
+    /// Asks the machine to exit this state and resume the suspended one
+    fn should_pop(&mut self) -> bool {
+        false
+    }
+    fn paused(&mut self) {}
+    fn resumed(&mut self) {}
+
+    // Timing methods
+    /// Minimum time spent in this state before the machine leaves it
+    fn min_dwell(&self) -> Duration {
+        Duration::ZERO
+    }
+    /// Transitions to the given state once this state has been active
+    /// for the given duration without requesting another state
+    fn after(&self) -> Option<(Duration, Self::StatesEnum)> {
+        None
+    }
+
+    // Debug methods
+    /// States this state can request, only used to draw the state graph
+    fn transitions(&self) -> Vec<Self::StatesEnum> {
+        vec![]
+    }
+
+    // Update methods
+    fn input(&mut self, _event: Input) {}
     fn process(&mut self, _delta: f32) {}
     fn physics_process(&mut self, _delta: f32) {}
-
-    fn destroy(&mut self) {}
 }
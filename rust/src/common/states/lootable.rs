@@ -1,5 +1,6 @@
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
+use fsm_macros::StateMachine;
 use godot::{
     classes::{CollisionObject3D, Node3D},
     global::godot_error,
@@ -15,13 +16,16 @@ use thiserror::Error;
 
 use crate::{
     common::{
-        finite_state_machine::FiniteStateMachine,
+        finite_state_machine::{
+            FiniteStateMachine, StateTimer, TimerTick, godot_adapter::GodotInput,
+            trace::TransitionTrace,
+        },
         inventory::{Inventory, InventorySlot},
     },
     impl_inode3d_for_fsm,
 };
 
-use super::State;
+use super::{State, StateBase};
 
 pub mod hover;
 pub mod hover_listener;
@@ -33,7 +37,7 @@ pub mod loot_state;
 
 pub type LootMachineContext = Rc<RefCell<LootContext>>;
 
-type DynState = Box<dyn State<Context = LootMachineContext, StatesEnum = LootState>>;
+type DynState = Box<dyn State<GodotInput, Context = LootMachineContext, StatesEnum = LootState>>;
 
 type StateMap = HashMap<LootState, DynState>;
 
@@ -80,7 +84,7 @@ impl LootContext {
     }
 }
 
-#[derive(Debug, GodotClass)]
+#[derive(Debug, GodotClass, StateMachine)]
 #[class(init, base = Node3D)]
 pub struct LootMachine {
     #[base]
@@ -90,6 +94,9 @@ pub struct LootMachine {
     states: StateMap,
     current_state: LootState,
     transitioning: bool,
+    state_timer: StateTimer,
+    state_stack: Vec<LootState>,
+    trace: TransitionTrace,
 }
 
 impl_inode3d_for_fsm!(LootMachine);
@@ -104,18 +111,29 @@ impl LootMachine {
     pub fn start(&mut self, context: LootMachineContext) {
         self.context = context;
     }
+}
+
+impl FiniteStateMachine for LootMachine {
+    type StatesEnum = LootState;
+    type Context = LootMachineContext;
+    type Input = GodotInput;
+
+    const TIMER_TICK: TimerTick = TimerTick::Process;
 
-    fn register_state(&mut self, state: DynState, states: &mut StateMap) {
-        let state_name = state.get_state_name();
-        states.insert(state_name, state);
+    fn ready(&mut self) {
+        self.states = self.setup_states(self.context.clone());
+        self.set_current_state(LootState::Idle);
+
+        if let Err(error) = self.transition_to_state(LootState::Idle) {
+            self.on_transition_error(error);
+        }
     }
 
-    fn destroy(&mut self) {
-        self.states.iter_mut().for_each(|(_, state)| {
-            state.destroy();
-        });
-        self.states.clear();
+    fn is_final_state(&self, state: &Self::StatesEnum) -> bool {
+        *state == LootState::Destroy
+    }
 
+    fn release_context(&mut self) {
         let mut context_borrow = self
             .context
             .try_borrow_mut()
@@ -128,19 +146,10 @@ impl LootMachine {
         drop(context_borrow);
 
         let _ = self.context.take();
-
-        self.base_mut().queue_free();
     }
-}
-
-impl FiniteStateMachine for LootMachine {
-    type StatesEnum = LootState;
-    type Context = LootMachineContext;
 
-    fn ready(&mut self) {
-        self.states = self.setup_states(self.context.clone());
-        self.set_current_state(LootState::Idle);
-        self.transition_to_state(LootState::Idle);
+    fn free_machine(&mut self) {
+        self.base_mut().queue_free();
     }
 
     fn setup_states(
@@ -148,7 +157,7 @@ impl FiniteStateMachine for LootMachine {
         context: Self::Context,
     ) -> std::collections::HashMap<
         Self::StatesEnum,
-        Box<dyn super::State<Context = Self::Context, StatesEnum = Self::StatesEnum>>,
+        Box<dyn super::State<Self::Input, Context = Self::Context, StatesEnum = Self::StatesEnum>>,
     > {
         let mut states: StateMap = HashMap::new();
 
@@ -169,10 +178,6 @@ impl FiniteStateMachine for LootMachine {
     }
 
     fn set_current_state(&mut self, state: Self::StatesEnum) {
-        if state == LootState::Destroy {
-            return self.destroy();
-        }
-
         self.current_state = state;
     }
 
@@ -184,12 +189,68 @@ impl FiniteStateMachine for LootMachine {
         self.transitioning
     }
 
+    fn get_state_stack(&mut self) -> &mut Vec<Self::StatesEnum> {
+        &mut self.state_stack
+    }
+
+    fn get_state_timer(&mut self) -> &mut StateTimer {
+        &mut self.state_timer
+    }
+
+    fn get_trace(&mut self) -> Option<&mut TransitionTrace> {
+        Some(&mut self.trace)
+    }
+
     fn get_states_map(
         &mut self,
     ) -> &mut std::collections::HashMap<
         Self::StatesEnum,
-        Box<dyn super::State<Context = Self::Context, StatesEnum = Self::StatesEnum>>,
+        Box<dyn super::State<Self::Input, Context = Self::Context, StatesEnum = Self::StatesEnum>>,
     > {
         &mut self.states
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Hover, Idle, Inspect, LootMachineContext, LootState, State, StateBase};
+
+    #[test]
+    fn loot_states_only_allow_their_transitions() {
+        let context = LootMachineContext::default();
+
+        assert_eq!(
+            Idle::new(context.clone()).transitions(),
+            vec![LootState::Hover]
+        );
+        assert_eq!(
+            Hover::new(context.clone()).transitions(),
+            vec![LootState::Idle, LootState::Inspect]
+        );
+        assert_eq!(
+            Inspect::new(context).transitions(),
+            vec![LootState::Destroy]
+        );
+    }
+
+    #[test]
+    fn hover_returns_to_idle_when_resumed_after_the_mouse_left() {
+        let mut hover = Hover::new(LootMachineContext::default());
+
+        hover.paused();
+        assert!(!hover.is_active());
+        assert_eq!(hover.get_next_state(), Some(LootState::Hover));
+
+        hover.resumed();
+        assert!(hover.is_active());
+        assert_eq!(hover.get_next_state(), Some(LootState::Idle));
+    }
+
+    #[test]
+    fn inspect_is_pushed_on_top_of_hover() {
+        let mut inspect = Inspect::new(LootMachineContext::default());
+
+        assert!(inspect.is_pushdown());
+        assert!(!inspect.should_pop());
+    }
+}
@@ -1,5 +1,6 @@
 use std::{cell::RefCell, rc::Rc};
 
+use fsm_macros::State;
 use godot::{
     classes::InputEvent,
     global::godot_error,
@@ -7,117 +8,100 @@ use godot::{
 };
 use thiserror::Error;
 
-use crate::common::states::State;
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase, StateError},
+};
 
 use super::{LootMachineContext, hover_listener::HoverListener, loot_state::LootState};
 
 #[derive(Debug, Error)]
 pub enum LootMenuHoverStateError {
-    #[error("The next_state could not be borrowed")]
-    NextState,
-
-    #[error("The active flag could not be borrowed")]
-    ActiveFlag,
+    #[error(transparent)]
+    State(#[from] StateError),
 
     #[error("The context could not be borrowed")]
     Context,
 
     #[error("The collision object was None, it should not be missing")]
     CollisionObjectMissing,
+
+    #[error("The hovering flag could not be borrowed")]
+    Hovering,
 }
 
-#[derive(Debug)]
+#[derive(Debug, State)]
+#[state(name = LootState::Hover)]
 pub struct Hover {
+    #[state(context, release)]
     context: LootMachineContext,
+    #[state(next_state, release)]
     next_state: Rc<RefCell<Option<LootState>>>,
+    #[state(active, release)]
     active: Rc<RefCell<bool>>,
+    /// Whether the mouse is over the collision object, also tracked while
+    /// the state is paused
+    #[state(release)]
+    hovering: Rc<RefCell<bool>>,
     connected: bool,
 }
 
 impl Hover {
-    fn set_active(&mut self, is_active: bool) {
-        let borrow = self
-            .active
-            .try_borrow_mut()
-            .map_err(|_| LootMenuHoverStateError::ActiveFlag);
-
-        match borrow {
-            Ok(mut active) => {
-                *active = is_active;
-            }
-            Err(error) => godot_error!("{error}"),
-        }
-    }
-
     fn get_listener(&self) -> Gd<HoverListener> {
         let mut listener = HoverListener::new_alloc();
         listener.bind_mut().next_state = self.next_state.clone();
         listener.bind_mut().active = self.active.clone();
+        listener.bind_mut().hovering = self.hovering.clone();
 
         listener
     }
-}
-
-impl State for Hover {
-    type StatesEnum = LootState;
-    type Context = LootMachineContext;
 
-    fn new(context: Self::Context) -> Self {
-        Hover {
-            context,
-            next_state: Rc::new(RefCell::new(None)),
-            active: Rc::new(RefCell::new(false)),
-            connected: false,
+    fn set_hovering(&mut self, is_hovering: bool) {
+        match self.hovering.try_borrow_mut() {
+            Ok(mut hovering) => *hovering = is_hovering,
+            Err(_) => godot_error!("{}", LootMenuHoverStateError::Hovering),
         }
     }
 
-    fn destroy(&mut self) {
-        let _ = self.next_state.take();
-        let _ = self.active.take();
-        let _ = self.context.take();
+    fn is_hovering(&self) -> bool {
+        match self.hovering.try_borrow() {
+            Ok(hovering) => *hovering,
+            Err(_) => false,
+        }
     }
+}
 
-    fn get_state_name(&self) -> Self::StatesEnum {
-        LootState::Hover
+impl State<GodotInput> for Hover {
+    fn transitions(&self) -> Vec<LootState> {
+        vec![LootState::Idle, LootState::Inspect]
     }
 
-    fn set_next_state(&mut self, state: Self::StatesEnum) {
-        let next_state_borrow = self
-            .next_state
-            .try_borrow_mut()
-            .map_err(|_| LootMenuHoverStateError::NextState);
+    fn exit(&mut self) {
+        self.set_next_state(LootState::Hover);
+        self.set_active(false);
+    }
 
-        match next_state_borrow {
-            Ok(mut next_state) => {
-                *next_state = Some(state);
-            }
-            Err(error) => godot_error!("{error}"),
-        }
+    fn paused(&mut self) {
+        self.set_next_state(LootState::Hover);
+        self.set_active(false);
     }
 
-    fn get_next_state(&mut self) -> Option<Self::StatesEnum> {
-        let next_state_borrow = self
-            .next_state
-            .try_borrow_mut()
-            .map_err(|_| LootMenuHoverStateError::NextState);
+    fn resumed(&mut self) {
+        self.set_active(true);
 
-        match next_state_borrow {
-            Ok(next_state) => next_state.clone(),
-            Err(error) => {
-                godot_error!("{error}");
-                None
-            }
+        // NOTE: The mouse usually leaves the collision object while the
+        // loot menu is open
+        if !self.is_hovering() {
+            self.set_next_state(LootState::Idle);
         }
     }
 
-    fn exit(&mut self) {
-        self.set_next_state(LootState::Hover);
-        self.set_active(false);
-    }
-
     fn enter(&mut self) {
         self.set_active(true);
 
+        // Idle only enters Hover once the mouse entered the collision object
+        self.set_hovering(true);
+
         if self.connected {
             return;
         }
@@ -145,6 +129,15 @@ impl State for Hover {
                             },
                         );
 
+                        collision_object.signals().mouse_entered().connect_obj(
+                            &self.get_listener(),
+                            |this: &mut HoverListener| {
+                                let _ = this
+                                    .mouse_entered()
+                                    .map_err(|error| godot_error!("{error}"));
+                            },
+                        );
+
                         collision_object.signals().mouse_exited().connect_obj(
                             &self.get_listener(),
                             |this: &mut HoverListener| {
@@ -7,6 +7,8 @@ use godot::{
     prelude::{GodotClass, godot_api},
 };
 
+use crate::common::states::StateError;
+
 use super::{hover::LootMenuHoverStateError, loot_state::LootState};
 
 #[derive(GodotClass)]
@@ -14,6 +16,7 @@ use super::{hover::LootMenuHoverStateError, loot_state::LootState};
 pub struct HoverListener {
     pub next_state: Rc<RefCell<Option<LootState>>>,
     pub active: Rc<RefCell<bool>>,
+    pub hovering: Rc<RefCell<bool>>,
     base: Base<Node3D>,
 }
 
@@ -29,7 +32,7 @@ impl HoverListener {
         let borrow = self
             .active
             .try_borrow_mut()
-            .map_err(|_| LootMenuHoverStateError::ActiveFlag);
+            .map_err(|_| StateError::ActiveFlag);
 
         match borrow {
             Ok(active) => *active,
@@ -60,7 +63,7 @@ impl HoverListener {
             let borrow = self
                 .next_state
                 .try_borrow_mut()
-                .map_err(|_| LootMenuHoverStateError::NextState);
+                .map_err(|_| StateError::NextState);
 
             match borrow {
                 Ok(mut next_state) => *next_state = Some(LootState::Inspect),
@@ -71,7 +74,25 @@ impl HoverListener {
         Ok(())
     }
 
+    /// Records whether the mouse is over the collision object, even while
+    /// the state is paused so it can be checked when the state resumes
+    fn set_hovering(&mut self, is_hovering: bool) -> Result<(), LootMenuHoverStateError> {
+        let mut hovering = self
+            .hovering
+            .try_borrow_mut()
+            .map_err(|_| LootMenuHoverStateError::Hovering)?;
+        *hovering = is_hovering;
+
+        Ok(())
+    }
+
+    pub fn mouse_entered(&mut self) -> Result<(), LootMenuHoverStateError> {
+        self.set_hovering(true)
+    }
+
     pub fn mouse_exited(&mut self) -> Result<(), LootMenuHoverStateError> {
+        self.set_hovering(false)?;
+
         let active = self.get_active();
         if !active {
             return Ok(());
@@ -80,7 +101,7 @@ impl HoverListener {
         let borrow = self
             .next_state
             .try_borrow_mut()
-            .map_err(|_| LootMenuHoverStateError::NextState);
+            .map_err(|_| StateError::NextState);
 
         match borrow {
             Ok(mut next_state) => *next_state = Some(LootState::Idle),
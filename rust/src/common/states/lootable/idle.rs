@@ -1,44 +1,31 @@
 use std::{cell::RefCell, rc::Rc};
 
+use fsm_macros::State;
 use godot::{
     global::{godot_error, godot_print},
     obj::{Gd, NewAlloc},
 };
-use thiserror::Error;
 
-use crate::common::states::State;
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase},
+};
 
 use super::{LootMachineContext, idle_listener::IdleListener, loot_state::LootState};
 
-#[derive(Debug, Error)]
-pub enum LootMenuIdleStateError {
-    #[error("Error borrowing next_state pointer")]
-    NextState,
-    #[error("Error borrowing active flag")]
-    ActiveFlag,
-}
-
-#[derive(Debug)]
+#[derive(Debug, State)]
+#[state(name = LootState::Idle)]
 pub struct Idle {
+    #[state(context, release)]
     context: LootMachineContext,
+    #[state(next_state, release)]
     next_state: Rc<RefCell<Option<LootState>>>,
     connected: bool,
+    #[state(active, release)]
     active: Rc<RefCell<bool>>,
 }
 
 impl Idle {
-    fn set_active(&mut self, is_active: bool) {
-        let borrow = self
-            .active
-            .try_borrow_mut()
-            .map_err(|_| LootMenuIdleStateError::NextState);
-
-        match borrow {
-            Ok(mut active) => *active = is_active,
-            Err(error) => godot_error!("{error}"),
-        }
-    }
-
     fn get_listener(&self) -> Gd<IdleListener> {
         let mut idle_listener = IdleListener::new_alloc();
 
@@ -53,54 +40,9 @@ impl Idle {
     }
 }
 
-impl State for Idle {
-    type StatesEnum = LootState;
-    type Context = LootMachineContext;
-
-    fn new(context: Self::Context) -> Self {
-        Idle {
-            context,
-            next_state: Rc::new(RefCell::new(None)),
-            active: Rc::new(RefCell::new(false)),
-            connected: false,
-        }
-    }
-
-    fn destroy(&mut self) {
-        let _ = self.next_state.take();
-        let _ = self.active.take();
-        let _ = self.context.take();
-    }
-
-    fn get_state_name(&self) -> Self::StatesEnum {
-        LootState::Idle
-    }
-
-    fn set_next_state(&mut self, state: Self::StatesEnum) {
-        let borrow = self
-            .next_state
-            .try_borrow_mut()
-            .map_err(|_| LootMenuIdleStateError::NextState);
-
-        match borrow {
-            Ok(mut next_state) => *next_state = Some(state),
-            Err(error) => godot_error!("{error}"),
-        }
-    }
-
-    fn get_next_state(&mut self) -> Option<Self::StatesEnum> {
-        let borrow = self
-            .next_state
-            .try_borrow_mut()
-            .map_err(|_| LootMenuIdleStateError::NextState);
-
-        match borrow {
-            Ok(next_state) => next_state.clone(),
-            Err(error) => {
-                godot_error!("{error}");
-                None
-            }
-        }
+impl State<GodotInput> for Idle {
+    fn transitions(&self) -> Vec<LootState> {
+        vec![LootState::Hover]
     }
 
     fn exit(&mut self) {
@@ -7,7 +7,9 @@ use godot::{
     prelude::{GodotClass, godot_api},
 };
 
-use super::{idle::LootMenuIdleStateError, loot_state::LootState};
+use crate::common::states::StateError;
+
+use super::loot_state::LootState;
 
 #[derive(GodotClass)]
 #[class(init, base = Node3D)]
@@ -25,7 +27,7 @@ impl IdleListener {
     #[signal]
     fn dummy();
 
-    pub fn mouse_entered(&mut self) -> Result<(), LootMenuIdleStateError> {
+    pub fn mouse_entered(&mut self) -> Result<(), StateError> {
         // NOTE: Using self active bool to stop the state changes
         // because godot-rust does not yet have a disconnect()
         // function for the input_event() signal implemented
@@ -33,7 +35,7 @@ impl IdleListener {
         let borrow = self
             .active
             .try_borrow_mut()
-            .map_err(|_| LootMenuIdleStateError::ActiveFlag);
+            .map_err(|_| StateError::ActiveFlag);
 
         let active = match borrow {
             Ok(active) => *active,
@@ -51,7 +53,7 @@ impl IdleListener {
         let borrow = self
             .next_state
             .try_borrow_mut()
-            .map_err(|_| LootMenuIdleStateError::NextState);
+            .map_err(|_| StateError::NextState);
 
         match borrow {
             Ok(mut next_state) => {
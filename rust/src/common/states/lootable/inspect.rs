@@ -3,6 +3,7 @@ use std::{
     rc::Rc,
 };
 
+use fsm_macros::State;
 use godot::{
     builtin::Vector2,
     classes::{InputEvent, InputEventMouseButton},
@@ -11,7 +12,11 @@ use godot::{
 };
 use thiserror::Error;
 
-use crate::common::{states::State, ui::loot_menu::LootMenu};
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase, StateError},
+    ui::loot_menu::LootMenu,
+};
 
 use super::{
     LootContext, LootMachineContext, inspect_listener::InspectListener, loot_state::LootState,
@@ -33,24 +38,33 @@ pub enum InspectError<'a> {
     ColliderInstanceInvalid,
     #[error("There was an error adding options to loot menu")]
     LootMenu,
-    #[error("Borrow error borrowing state active flag")]
-    ActiveFlag,
-    #[error("Borrow error borrowing next_state pointer")]
-    NextState,
+    #[error(transparent)]
+    State(#[from] StateError),
     #[error("Borrow error borrowing mouse_hovering flag")]
     HoveringFlag,
     #[error("Borrow error borrowing trigger_menu flag")]
     TriggerMenu,
+    #[error("Borrow error borrowing pop_requested flag")]
+    PopRequested,
 }
 
-#[derive(Debug)]
+#[derive(Debug, State)]
+#[state(name = LootState::Inspect)]
 pub struct Inspect {
+    #[state(context, release)]
     context: LootMachineContext,
+    #[state(next_state, release)]
     next_state: Rc<RefCell<Option<LootState>>>,
+    #[state(active, release)]
     active: Rc<RefCell<bool>>,
+    #[state(release)]
     menu: Rc<RefCell<Option<Gd<LootMenu>>>>,
+    #[state(release)]
     mouse_hovering: Rc<RefCell<bool>>,
+    #[state(release)]
     trigger_menu: Rc<RefCell<bool>>,
+    #[state(release)]
+    pop_requested: Rc<RefCell<bool>>,
     connected: bool,
     destroyed: bool,
 }
@@ -171,20 +185,11 @@ impl Inspect {
         listener.bind_mut().mouse_hovering = self.mouse_hovering.clone();
         listener.bind_mut().trigger_menu = self.trigger_menu.clone();
         listener.bind_mut().menu = self.menu.clone();
+        listener.bind_mut().pop_requested = self.pop_requested.clone();
 
         listener
     }
 
-    fn set_active(&mut self) {
-        let borrow = self.active.try_borrow_mut();
-        match borrow {
-            Ok(mut active) => {
-                *active = true;
-            }
-            Err(error) => godot_error!("{error}"),
-        }
-    }
-
     fn set_trigger(&mut self, trigger: bool) {
         let trigger_borrow = self.trigger_menu.try_borrow_mut();
 
@@ -197,6 +202,18 @@ impl Inspect {
         }
     }
 
+    fn set_pop_requested(&mut self, pop_requested: bool) {
+        let borrow = self
+            .pop_requested
+            .try_borrow_mut()
+            .map_err(|_| InspectError::PopRequested);
+
+        match borrow {
+            Ok(mut pop_requested_ref) => *pop_requested_ref = pop_requested,
+            Err(error) => godot_error!("{error}"),
+        }
+    }
+
     fn add_mouse_entered_listener(&mut self, context: &mut RefMut<LootContext>) {
         let listener = self.get_signal_listener();
 
@@ -252,93 +269,39 @@ impl Inspect {
                 None => godot_error!("Loot menu is None, could not close menu"),
             }
 
-            self.set_next_state(LootState::Idle);
-        }
-    }
-
-    fn is_active(&self) -> bool {
-        let active_refcell = self.active.clone();
-
-        match active_refcell.try_borrow() {
-            Ok(active) => *active,
-            Err(_) => false,
+            self.set_pop_requested(true);
         }
     }
 }
 
-impl State for Inspect {
-    type StatesEnum = LootState;
-    type Context = LootMachineContext;
-
-    fn new(context: Self::Context) -> Self {
-        Inspect {
-            context,
-            next_state: Rc::new(RefCell::new(None)),
-            active: Rc::new(RefCell::new(false)),
-            connected: false,
-            menu: Rc::new(RefCell::new(None)),
-            mouse_hovering: Rc::new(RefCell::new(false)),
-            trigger_menu: Rc::new(RefCell::new(false)),
-            destroyed: false,
-        }
-    }
-
-    fn destroy(&mut self) {
-        let _ = self.next_state.take();
-        let _ = self.active.take();
-        let _ = self.context.take();
-        let _ = self.menu.take();
-        let _ = self.mouse_hovering.take();
-        let _ = self.trigger_menu.take();
+impl State<GodotInput> for Inspect {
+    fn is_pushdown(&self) -> bool {
+        true
     }
 
-    fn get_state_name(&self) -> Self::StatesEnum {
-        LootState::Inspect
+    fn transitions(&self) -> Vec<LootState> {
+        vec![LootState::Destroy]
     }
 
-    fn set_next_state(&mut self, state: Self::StatesEnum) {
-        let borrow = self
-            .next_state
-            .try_borrow_mut()
-            .map_err(|_| InspectError::NextState);
-
-        match borrow {
-            Ok(mut next_state) => *next_state = Some(state),
-            Err(error) => godot_error!("{error}"),
-        }
-    }
-
-    fn get_next_state(&mut self) -> Option<Self::StatesEnum> {
-        let borrow = self
-            .next_state
-            .try_borrow_mut()
-            .map_err(|_| InspectError::NextState);
-
-        match borrow {
-            Ok(next_state) => next_state.clone(),
-            Err(error) => {
-                godot_error!("{error}");
-                None
-            }
+    fn should_pop(&mut self) -> bool {
+        match self.pop_requested.try_borrow() {
+            Ok(pop_requested) => *pop_requested,
+            Err(_) => false,
         }
     }
 
     fn exit(&mut self) {
-        let active_refcell = self.active.clone();
-        let mut borrow = active_refcell.try_borrow_mut();
-        if let Ok(active) = &mut borrow {
-            **active = false;
-        }
-
+        self.set_active(false);
+        self.set_pop_requested(false);
         self.set_next_state(LootState::Inspect);
 
-        godot_print!("disabled idle state");
+        godot_print!("disabled inspect state");
     }
 
     fn enter(&mut self) {
         godot_print!("Inspect:: enter()");
 
-        self.set_active();
+        self.set_active(true);
         self.set_trigger(true);
 
         if self.connected {
@@ -437,3 +400,17 @@ impl State for Inspect {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Inspect, State, StateBase};
+
+    #[test]
+    fn pops_once_the_menu_requested_it() {
+        let mut inspect = Inspect::new(Default::default());
+        assert!(!inspect.should_pop());
+
+        *inspect.pop_requested.borrow_mut() = true;
+        assert!(inspect.should_pop());
+    }
+}
@@ -6,7 +6,7 @@ use godot::{
     prelude::{GodotClass, godot_api},
 };
 
-use crate::common::ui::loot_menu::LootMenu;
+use crate::common::{states::StateError, ui::loot_menu::LootMenu};
 
 use super::{LootContext, inspect::InspectError, loot_state::LootState};
 
@@ -19,6 +19,7 @@ pub struct InspectListener {
     pub trigger_menu: Rc<RefCell<bool>>,
     pub context: Rc<RefCell<LootContext>>,
     pub menu: Rc<RefCell<Option<Gd<LootMenu>>>>,
+    pub pop_requested: Rc<RefCell<bool>>,
     base: Base<Node3D>,
 }
 
@@ -60,7 +61,7 @@ impl InspectListener {
         let mut active = self
             .active
             .try_borrow_mut()
-            .map_err(|_| InspectError::ActiveFlag)?;
+            .map_err(|_| StateError::ActiveFlag)?;
 
         *active = false;
 
@@ -73,15 +74,20 @@ impl InspectListener {
             .as_ref()
             .ok_or(InspectError::MenuShouldNotBeNone)?;
 
-        let mut next_state = self
-            .next_state
-            .try_borrow_mut()
-            .map_err(|_| InspectError::NextState)?;
-
         if loot_menu.bind().len() == 1 {
+            let mut next_state = self
+                .next_state
+                .try_borrow_mut()
+                .map_err(|_| StateError::NextState)?;
+
             *next_state = Some(LootState::Destroy);
         } else {
-            *next_state = Some(LootState::Idle);
+            let mut pop_requested = self
+                .pop_requested
+                .try_borrow_mut()
+                .map_err(|_| InspectError::PopRequested)?;
+
+            *pop_requested = true;
         }
 
         *loot_menu_opt = None;
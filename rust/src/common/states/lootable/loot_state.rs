@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 #[derive(Default, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum LootState {
     #[default]
@@ -6,3 +8,9 @@ pub enum LootState {
     Inspect,
     Destroy,
 }
+
+impl Display for LootState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
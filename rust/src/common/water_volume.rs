@@ -0,0 +1,56 @@
+use godot::{
+    builtin::Vector3,
+    classes::{Area3D, BoxShape3D, CollisionShape3D, IArea3D, Node},
+    obj::{Base, Gd, Inherits, WithBaseField},
+    prelude::{GodotClass, godot_api},
+};
+
+/// Group every WaterVolume adds itself to, used by the movement machine to
+/// find the water the player is in
+pub const WATER_VOLUME_GROUP: &str = "water_volume";
+
+#[derive(GodotClass, Debug)]
+#[class(base=Area3D, init)]
+/// WaterVolume marks the space of its BoxShape3D children as water, the
+/// top of the highest box is the water surface.
+pub struct WaterVolume {
+    base: Base<Area3D>,
+
+    #[export(range=(0.0, 20.0))]
+    /// How strongly the water pushes a submerged player towards the surface
+    pub buoyancy: f32,
+}
+
+#[godot_api]
+impl IArea3D for WaterVolume {
+    fn ready(&mut self) {
+        self.base_mut()
+            .add_to_group_ex(WATER_VOLUME_GROUP)
+            .persistent(false)
+            .done();
+    }
+}
+
+impl WaterVolume {
+    /// Global height of the water surface
+    pub fn get_surface_height(&self) -> f32 {
+        let base = self.base();
+
+        base.get_children()
+            .iter_shared()
+            .filter_map(|child| child.try_cast::<CollisionShape3D>().ok())
+            .filter_map(|collision_shape| {
+                let shape = collision_shape.get_shape()?.try_cast::<BoxShape3D>().ok()?;
+                let top = Vector3::new(0., shape.get_size().y / 2., 0.);
+
+                Some((collision_shape.get_global_transform() * top).y)
+            })
+            .reduce(f32::max)
+            .unwrap_or(base.get_global_position().y)
+    }
+
+    /// Whether `body` is inside the water
+    pub fn contains<T: Inherits<Node>>(&self, body: &Gd<T>) -> bool {
+        self.base().overlaps_body(body)
+    }
+}
@@ -3,9 +3,7 @@ use godot::init::gdextension;
 mod actions;
 mod debug;
 mod items;
-mod motion_signals;
 mod movement;
-mod movement_animations;
 mod npc;
 mod player;
 mod shell;
@@ -20,5 +18,13 @@ fn main() {
     struct MyExtension;
 
     #[gdextension]
-    unsafe impl ExtensionLibrary for MyExtension {}
+    unsafe impl ExtensionLibrary for MyExtension {
+        fn on_level_init(level: InitLevel) {
+            if level == InitLevel::Scene {
+                common::states::set_state_error_hook(
+                    common::finite_state_machine::godot_adapter::log_state_error,
+                );
+            }
+        }
+    }
 }
@@ -1,17 +1,25 @@
-use std::sync::LazyLock;
-
 use godot::classes::{AnimationPlayer, CharacterBody3D, Input, ProjectSettings};
 use godot::prelude::*;
 
-use crate::actions::Actions;
-use crate::motion_signals::MotionSignals;
+use crate::actions::ACTIONS;
 
 const DEFAULT_GRAVITY: f32 = 9.8;
 const DEFAULT_GRAVITY_VECTOR: Vector3 = Vector3::new(0., -1., 0.);
 const GRAVITY_VECTOR_SETTINGS_PATH: &str = "physics/3d/default_gravity_vector";
 const GRAVITY_SETTINGS_PATH: &str = "physics/3d/default_gravity";
 
-static ACTIONS: LazyLock<Actions> = LazyLock::new(Actions::default);
+/// Gravity from the project settings, the default gravity vector scaled
+/// by the default gravity
+pub(crate) fn project_gravity() -> Vector3 {
+    let settings = ProjectSettings::singleton();
+    let g = settings.get_setting(GRAVITY_VECTOR_SETTINGS_PATH);
+    let gravity_vector: Vector3 = g.try_to().unwrap_or(DEFAULT_GRAVITY_VECTOR);
+
+    let g = settings.get_setting(GRAVITY_SETTINGS_PATH);
+    let gravity: f32 = g.try_to().unwrap_or(DEFAULT_GRAVITY);
+
+    gravity_vector * gravity
+}
 
 #[derive(GodotClass)]
 #[class(base=Node3D, init)]
@@ -50,9 +58,6 @@ struct Movement {
     #[export(range=(1., 200.))]
     jump_force: f32,
 
-    #[export]
-    motion_signals: Option<Gd<MotionSignals>>,
-
     // Whether the player is currently jumping
     jumping: bool,
 
@@ -68,14 +73,10 @@ struct Movement {
 
 impl Movement {
     fn apply_jump(&mut self, input: &Gd<Input>, node: &mut Gd<CharacterBody3D>, delta: f64) {
-        let settings = ProjectSettings::singleton();
-        let g = settings.get_setting(GRAVITY_VECTOR_SETTINGS_PATH);
-        let gravity_vector: Vector3 = g.try_to().unwrap_or(DEFAULT_GRAVITY_VECTOR);
-
-        let g = settings.get_setting(GRAVITY_SETTINGS_PATH);
-        let gravity: f32 = g.try_to().unwrap_or(DEFAULT_GRAVITY);
+        let gravity_vector = project_gravity();
+        let gravity = gravity_vector.length();
 
-        let jump_impulse = (gravity_vector * gravity * self.jump_force) * -1.;
+        let jump_impulse = (gravity_vector * self.jump_force) * -1.;
 
         if input.is_action_just_pressed(ACTIONS.jump) && node.is_on_floor() {
             self.instant_velocity.y = jump_impulse.y * delta as f32;
@@ -1,9 +1,11 @@
 use godot::{
     classes::{IRigidBody3D, InputEvent, PanelContainer, RigidBody3D},
-    obj::{Base, Gd},
+    obj::{Base, Gd, WithBaseField, WithUserSignals},
     prelude::{GodotClass, godot_api},
 };
 
+use crate::player::{PLAYER_GROUP, Player3D};
+
 #[derive(Debug, GodotClass)]
 #[class(init, base = RigidBody3D)]
 pub struct TestNpc {
@@ -12,11 +14,67 @@ pub struct TestNpc {
 
     #[export]
     loot_options: Option<Gd<PanelContainer>>,
+
+    #[export(range=(0.0, 50.0))]
+    /// How far away the NPC hears a standing player, scaled by the noise
+    /// level of the player
+    hearing_distance: f32,
+
+    hears_player: bool,
 }
 
 #[godot_api]
 impl IRigidBody3D for TestNpc {
     fn ready(&mut self) {}
 
+    fn physics_process(&mut self, _delta: f64) {
+        let heard_player = self.find_player().filter(|player| self.can_hear(player));
+
+        match (heard_player, self.hears_player) {
+            (Some(player), false) => {
+                self.hears_player = true;
+                self.signals().player_heard().emit(&player);
+            }
+            (None, true) => {
+                self.hears_player = false;
+                self.signals().player_lost().emit();
+            }
+            _ => {}
+        }
+    }
+
     fn input(&mut self, _event: Gd<InputEvent>) {}
 }
+
+#[godot_api]
+impl TestNpc {
+    #[signal]
+    /// The player came close enough for the NPC to hear it
+    pub fn player_heard(player: Gd<Player3D>);
+
+    #[signal]
+    /// The player moved out of hearing distance or became quiet enough
+    pub fn player_lost();
+}
+
+impl TestNpc {
+    /// Whether the player is within hearing distance, which shrinks while
+    /// the player is quiet, e.g. crouching
+    pub fn can_hear(&self, player: &Gd<Player3D>) -> bool {
+        let noise_level = player.bind().get_noise_level();
+        let distance = self
+            .base()
+            .get_global_position()
+            .distance_to(player.get_global_position());
+
+        distance <= self.hearing_distance * noise_level
+    }
+
+    fn find_player(&self) -> Option<Gd<Player3D>> {
+        let mut tree = self.base().get_tree()?;
+
+        tree.get_first_node_in_group(PLAYER_GROUP)?
+            .try_cast::<Player3D>()
+            .ok()
+    }
+}
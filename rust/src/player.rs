@@ -3,15 +3,20 @@ use godot::classes::{
     AudioStreamPlayer3D, Camera3D, CharacterBody3D, CsgMesh3D, ICharacterBody3D,
     PhysicsRayQueryParameters3D, StandardMaterial3D,
 };
+use godot::global::linear_to_db;
 use godot::obj::WithBaseField;
 use godot::prelude::*;
 use rand::Rng;
 
 use crate::common::proximity_detector::ProximityDetector;
 use crate::states::movement::context::MovementContext;
+use crate::states::movement::motion::water_depth;
 
 pub type StateContext = Gd<MovementContext>;
 
+/// Group the player adds itself to, used by NPCs to find the player
+pub const PLAYER_GROUP: &str = "player";
+
 #[derive(GodotClass)]
 #[class(base=CharacterBody3D, init)]
 #[allow(unused)]
@@ -20,6 +25,9 @@ pub struct Player3D {
     context: Option<StateContext>,
     base: Base<CharacterBody3D>,
     selected_item: Option<Gd<StandardMaterial3D>>,
+    /// Volume of the footstep player set in the scene, the volume of the
+    /// sound sets is relative to it
+    footstep_volume_db: f32,
 }
 
 #[godot_api]
@@ -40,6 +48,67 @@ impl Player3D {
         player_skin
     }
 
+    #[func]
+    /// How loud the player is to NPC perception, lowered while crouching
+    pub fn get_noise_level(&self) -> f32 {
+        self.get_context()
+            .map(|context| context.bind().noise_multiplier)
+            .unwrap_or(1.)
+    }
+
+    #[func]
+    /// Whether the player is in the invulnerability window of a dodge
+    pub fn is_invulnerable(&self) -> bool {
+        self.get_context()
+            .is_some_and(|context| context.bind().invulnerable)
+    }
+
+    /// Floor the player stands on and the point it touches it, taken from
+    /// the collisions of the last move_and_slide()
+    fn floor_contact(&self) -> Option<(Gd<Object>, Vector3)> {
+        let mut base = self.base().clone();
+        let floor_max_angle = base.get_floor_max_angle();
+
+        (0..base.get_slide_collision_count())
+            .filter_map(|index| base.get_slide_collision(index))
+            .find(|collision| collision.get_angle() <= floor_max_angle)
+            .and_then(|collision| Some((collision.get_collider()?, collision.get_position())))
+    }
+
+    /// Picks the footstep sound for the floor under the player and scales its
+    /// volume by the movement speed
+    fn prepare_footstep(&self, context: &MovementContext, audio: &mut Gd<AudioStreamPlayer3D>) {
+        let Some(table) = context.footstep_sounds.clone() else {
+            return;
+        };
+        let table = table.bind();
+
+        let in_water = water_depth(context, 0.).is_some_and(|depth| depth > 0.);
+        let water_set = table.water_set.clone().filter(|_| in_water);
+
+        let sound_set = water_set.or_else(|| {
+            self.floor_contact()
+                .and_then(|(floor, position)| table.sound_set_for(&floor, position))
+        });
+
+        let Some(sound_set) = sound_set else {
+            return;
+        };
+        let sound_set = sound_set.bind();
+
+        if let Some(stream) = sound_set.random_stream() {
+            audio.set_stream(&stream);
+        }
+
+        let velocity = self.base().get_velocity();
+        let speed = Vector3::new(velocity.x, 0., velocity.z).length();
+        let volume_scale =
+            table.volume_scale(speed, context.movement_speed) * context.noise_multiplier;
+
+        let volume_db = linear_to_db(volume_scale.max(f32::EPSILON) as f64) as f32;
+        audio.set_volume_db(self.footstep_volume_db + sound_set.volume_db + volume_db);
+    }
+
     #[func]
     /// Plays a footstep sound using the footstep node path from the MovementContext
     fn footstep(&self) {
@@ -58,6 +127,8 @@ impl Player3D {
             return;
         };
 
+        self.prepare_footstep(&context.bind(), audio_stream_player_3d);
+
         let mut rng = rand::rng();
         let pitch_scale = rng.random_range(0.8..1.2);
         audio_stream_player_3d.set_pitch_scale(pitch_scale);
@@ -136,8 +207,20 @@ impl Player3D {
 impl ICharacterBody3D for Player3D {
     // Called when the node is ready in the scene tree.
     fn ready(&mut self) {
+        self.base_mut()
+            .add_to_group_ex(PLAYER_GROUP)
+            .persistent(false)
+            .done();
+
         let base = self.base().clone();
 
+        if let Some(context) = self.get_context() {
+            let footstep = context.bind().get_footstep();
+            self.footstep_volume_db = base
+                .try_get_node_as::<AudioStreamPlayer3D>(&footstep)
+                .map_or(0., |audio| audio.get_volume_db());
+        }
+
         // NOTE: Test code to test ProximityDetector component
         if let Some(ref mut items_detector) =
             base.try_get_node_as::<ProximityDetector>("ItemsDetector")
@@ -1,9 +1,9 @@
 use std::{cell::RefCell, rc::Rc, str::FromStr};
 
 use godot::{
-    builtin::{NodePath, Vector3},
+    builtin::{GString, NodePath, Vector3},
     classes::{CollisionObject3D, INode3D, Node, Node3D, PackedScene},
-    obj::{Base, Gd, NewAlloc, WithBaseField},
+    obj::{Base, Gd, NewAlloc, WithBaseField, WithUserSignals},
     prelude::{GodotClass, godot_api},
     tools::load,
 };
@@ -25,6 +25,7 @@ struct Shell {
     base: Base<Node3D>,
     level: Option<Gd<Node>>,
     inventory: Option<Rc<RefCell<Inventory>>>,
+    loot_machines: Vec<Gd<LootMachine>>,
 }
 
 #[godot_api]
@@ -135,7 +136,18 @@ impl Shell {
         // TODO: Re-do start() for LootMachine
         let mut item_loot_machine = LootMachine::new_alloc();
         item_loot_machine.bind_mut().start(loot_context_rc);
+
+        let machine_id = item_loot_machine.instance_id();
+        item_loot_machine.signals().finished().connect_obj(
+            &self.to_gd(),
+            move |this: &mut Shell, _state: GString| {
+                this.loot_machines
+                    .retain(|machine| machine.instance_id() != machine_id);
+            },
+        );
+
         self.base_mut().add_child(&item_loot_machine);
+        self.loot_machines.push(item_loot_machine);
     }
 }
 
@@ -1,29 +1,70 @@
+pub mod climbing;
 pub mod context;
+pub mod crouching;
+pub mod dodging;
+pub mod falling;
 pub mod idle;
+pub mod input_recording;
+pub mod jumping;
+pub mod landing;
+pub mod ledge_hang;
+pub mod motion;
 pub mod movement_states;
+pub mod sprinting;
+pub mod swimming;
 pub mod walking;
 
 use std::collections::HashMap;
 
 use context::MovementContext;
+use fsm_macros::StateMachine;
 use godot::{
-    classes::{AnimationPlayer, CharacterBody3D, Node, Node3D},
-    global::godot_print,
-    obj::{Base, Gd},
+    builtin::GString,
+    classes::{
+        AnimationPlayer, AnimationTree, Camera3D, CharacterBody3D, CollisionShape3D, Engine, Input,
+        Node, Node3D, Os,
+    },
+    global::{godot_error, godot_print, godot_warn},
+    obj::{Base, Gd, WithBaseField},
     prelude::{GodotClass, godot_api},
 };
+use input_recording::{
+    InputFrame, InputRecording, InputRecordingError, InputSource, from_array, to_array,
+};
 
 use crate::{
-    common::{finite_state_machine::FiniteStateMachine, states::State},
+    common::{
+        finite_state_machine::{
+            FiniteStateMachine, StateTimer,
+            godot_adapter::GodotInput,
+            regions::{OrthogonalRegion, RegionGuards, StateSnapshot},
+            trace::TransitionTrace,
+        },
+        states::{State, StateBase},
+    },
     impl_inode3d_for_fsm,
     player::StateContext,
-    states::movement::{idle::Idle, movement_states::MovementStates, walking::Walking},
+    states::movement::{
+        climbing::Climbing, crouching::Crouching, dodging::Dodging, falling::Falling, idle::Idle,
+        jumping::Jumping, landing::Landing, ledge_hang::LedgeHang, movement_states::MovementStates,
+        sprinting::Sprinting, swimming::Swimming, walking::Walking,
+    },
 };
 
-type DynState = Box<dyn State<Context = StateContext, StatesEnum = MovementStates>>;
+/// Records the player input to a file, e.g. `-- --record-input=user://stuck.json`
+const RECORD_INPUT_ARG: &str = "--record-input=";
+
+/// Replays a recorded file and quits once the playback finishes
+const REPLAY_INPUT_ARG: &str = "--replay-input=";
+
+/// How far the player may end up from the recorded final position
+const PLAYBACK_TOLERANCE: f32 = 0.001;
+
+type DynState = Box<dyn State<GodotInput, Context = StateContext, StatesEnum = MovementStates>>;
 type StateMap = HashMap<MovementStates, DynState>;
+type DynRegion = Box<dyn OrthogonalRegion<GodotInput, StateContext>>;
 
-#[derive(Debug, GodotClass)]
+#[derive(Debug, GodotClass, StateMachine)]
 #[class(init, base = Node3D)]
 pub struct MovementMachine {
     base: Base<Node3D>,
@@ -31,6 +72,20 @@ pub struct MovementMachine {
     context: StateContext,
     states: StateMap,
     transitioning: bool,
+    state_timer: StateTimer,
+    state_stack: Vec<MovementStates>,
+    trace: TransitionTrace,
+    regions: Vec<DynRegion>,
+    guards: RegionGuards<MovementStates>,
+    input_source: InputSource,
+    /// The context is only filled in by `start()`, physics frames before it
+    /// are skipped
+    started: bool,
+    /// The command line is read on the first physics frame, once the player
+    /// is inside the tree
+    input_args_read: bool,
+    /// Set when the playback was started from the command line
+    quit_after_playback: bool,
 
     #[allow(unused)]
     current_state: MovementStates,
@@ -42,10 +97,192 @@ impl MovementMachine {
     pub fn start(&mut self, context: Gd<MovementContext>, scene_tree: Gd<Node>) {
         self.context = context;
         self.context.bind_mut().set_scene_tree(scene_tree);
+
+        let max_stamina = self.context.bind().max_stamina;
+        self.context.bind_mut().stamina = max_stamina;
+        self.context.bind_mut().noise_multiplier = 1.;
+        self.context.bind_mut().speed_multiplier = 1.;
+        motion::consume_jump(&mut self.context.bind_mut());
+
+        let max_breath = self.context.bind().max_breath;
+        self.context.bind_mut().breath = max_breath;
+
         self.get_nodes();
+        motion::configure_floor(&self.context.bind());
+        motion::configure_animation_tree(&self.context.bind());
+        motion::configure_root_motion(&self.context.bind());
 
         self.states = self.setup_states(self.context.clone());
         self.set_current_state(MovementStates::Idle);
+        self.start_regions(self.context.clone());
+
+        self.started = true;
+    }
+
+    /// Runs a region (e.g. upper body actions) next to the locomotion states,
+    /// regions have to be added before `start()` which hands them the context
+    pub fn add_region(
+        &mut self,
+        region: impl OrthogonalRegion<GodotInput, StateContext> + 'static,
+    ) {
+        self.regions.push(Box::new(region));
+    }
+
+    /// Blocks locomotion transitions based on the state of the other regions
+    pub fn add_guard(&mut self, guard: impl Fn(&MovementStates, &StateSnapshot) -> bool + 'static) {
+        self.guards.push(guard);
+    }
+
+    /// Appends the input of every physics frame to a recording, which is
+    /// saved to `path` when the recording stops or the machine leaves the tree
+    pub fn start_input_recording(&mut self, path: GString) {
+        let context = self.context.bind();
+        let player = context.get_node(context.player_node.clone());
+        let player_scene = context.get_node(context.player_scene_node.clone());
+
+        let recording = InputRecording {
+            physics_ticks_per_second: Engine::singleton().get_physics_ticks_per_second(),
+            start_position: to_array(player.get_global_position()),
+            start_velocity: to_array(player.get_velocity()),
+            start_facing: player_scene.get_global_rotation().y,
+            final_position: None,
+            frames: Vec::new(),
+        };
+
+        drop(context);
+        self.input_source = InputSource::Recording {
+            recording,
+            path: path.to_string(),
+        };
+    }
+
+    /// Saves the recording and goes back to the live input
+    pub fn stop_input_recording(&mut self) -> Result<(), InputRecordingError> {
+        match std::mem::take(&mut self.input_source) {
+            InputSource::Recording {
+                mut recording,
+                path,
+            } => {
+                let context = self.context.bind();
+                let player = context.get_node(context.player_node.clone());
+                recording.final_position = Some(to_array(player.get_global_position()));
+
+                recording.save(&path)
+            }
+            source => {
+                self.input_source = source;
+                Ok(())
+            }
+        }
+    }
+
+    /// Feeds the frames of `recording` to the states in place of the live
+    /// input, starting from the recorded player position. The playback only
+    /// matches the recording when both start in the same state, e.g. at the
+    /// start of the level
+    pub fn start_input_playback(&mut self, recording: InputRecording) {
+        let ticks = Engine::singleton().get_physics_ticks_per_second();
+        if recording.physics_ticks_per_second != ticks {
+            godot_warn!(
+                "Input recording was made at {} physics ticks per second, playing it back at {ticks}",
+                recording.physics_ticks_per_second
+            );
+        }
+
+        let context = self.context.bind();
+        let mut player = context.get_node(context.player_node.clone());
+        let mut player_scene = context.get_node(context.player_scene_node.clone());
+
+        player.set_global_position(from_array(recording.start_position));
+        player.set_velocity(from_array(recording.start_velocity));
+
+        let mut rotation = player_scene.get_global_rotation();
+        rotation.y = recording.start_facing;
+        player_scene.set_global_rotation(rotation);
+
+        drop(context);
+        self.input_source = InputSource::Playback {
+            recording,
+            frame: 0,
+        };
+    }
+
+    fn start_input_source_from_args(&mut self) {
+        let args = Os::singleton().get_cmdline_user_args();
+
+        for arg in args.as_slice() {
+            let arg = arg.to_string();
+
+            if let Some(path) = arg.strip_prefix(RECORD_INPUT_ARG) {
+                self.start_input_recording(path.into());
+            } else if let Some(path) = arg.strip_prefix(REPLAY_INPUT_ARG) {
+                match InputRecording::load(path) {
+                    Ok(recording) => {
+                        self.quit_after_playback = true;
+                        self.start_input_playback(recording);
+                    }
+                    Err(error) => godot_error!("{error}"),
+                }
+            }
+        }
+    }
+
+    /// Input the states read this physics frame, advances the recording or
+    /// playback
+    fn update_input(&mut self) {
+        if !self.input_args_read {
+            self.input_args_read = true;
+            self.start_input_source_from_args();
+        }
+
+        let live = InputFrame::capture(&Input::singleton(), &self.context.bind());
+
+        match self.input_source.next_frame(live) {
+            Some(frame) => self.context.bind_mut().input = frame,
+            None => self.finish_input_playback(),
+        }
+    }
+
+    fn finish_input_playback(&mut self) {
+        let InputSource::Playback { recording, .. } = std::mem::take(&mut self.input_source) else {
+            return;
+        };
+
+        let position = {
+            let context = self.context.bind();
+            context
+                .get_node(context.player_node.clone())
+                .get_global_position()
+        };
+
+        let matches = match recording.final_position.map(from_array) {
+            Some(expected) if expected.distance_to(position) > PLAYBACK_TOLERANCE => {
+                godot_warn!(
+                    "Input playback finished at {position}, the recording finished at {expected}"
+                );
+                false
+            }
+            _ => {
+                godot_print!("Input playback finished at {position}");
+                true
+            }
+        };
+
+        // NOTE: Without input the player stands still until the live input
+        // takes over next frame
+        self.context.bind_mut().input = InputFrame::default();
+        self.context
+            .signals()
+            .input_playback_finished()
+            .emit(position);
+
+        if !self.quit_after_playback {
+            return;
+        }
+
+        if let Some(mut tree) = self.base().get_tree() {
+            tree.quit_ex().exit_code(if matches { 0 } else { 1 }).done();
+        }
     }
 
     fn get_nodes(&mut self) {
@@ -75,21 +312,39 @@ impl MovementMachine {
             panic!("Could not get animator");
         };
 
+        let Some(camera) = scene_tree.try_get_node_as::<Camera3D>(&context.get_camera()) else {
+            godot_print!("Couldn't get camera");
+            panic!("Could not get camera");
+        };
+
+        let Some(collision_shape) =
+            scene_tree.try_get_node_as::<CollisionShape3D>(&context.get_collision_shape())
+        else {
+            godot_print!("Couldn't get collision shape");
+            panic!("Could not get collision shape");
+        };
+
         context.player_node = Some(player);
         context.pivot_node = Some(pivot);
         context.player_scene_node = Some(player_scene);
         context.animator = Some(animator);
-    }
+        context.collision_shape_node = Some(collision_shape);
+        context.camera_node = Some(camera);
 
-    fn register_state(&mut self, state: DynState, states: &mut StateMap) {
-        let state_name = state.get_state_name();
-        states.insert(state_name, state);
+        let animation_tree_path = context.get_animation_tree();
+        context.animation_tree_node = match animation_tree_path.is_empty() {
+            true => None,
+            false => scene_tree.try_get_node_as::<AnimationTree>(&animation_tree_path),
+        };
     }
 }
 
 impl FiniteStateMachine for MovementMachine {
     type StatesEnum = MovementStates;
     type Context = StateContext;
+    type Input = GodotInput;
+
+    const REGION_NAME: &'static str = "locomotion";
 
     fn ready(&mut self) {}
 
@@ -104,6 +359,33 @@ impl FiniteStateMachine for MovementMachine {
         self.register_state(Box::new(Walking::new(context.clone())), &mut states);
         godot_print!("Created walking state");
 
+        self.register_state(Box::new(Sprinting::new(context.clone())), &mut states);
+        godot_print!("Created sprinting state");
+
+        self.register_state(Box::new(Crouching::new(context.clone())), &mut states);
+        godot_print!("Created crouching state");
+
+        self.register_state(Box::new(Jumping::new(context.clone())), &mut states);
+        godot_print!("Created jumping state");
+
+        self.register_state(Box::new(Falling::new(context.clone())), &mut states);
+        godot_print!("Created falling state");
+
+        self.register_state(Box::new(Landing::new(context.clone())), &mut states);
+        godot_print!("Created landing state");
+
+        self.register_state(Box::new(Swimming::new(context.clone())), &mut states);
+        godot_print!("Created swimming state");
+
+        self.register_state(Box::new(Climbing::new(context.clone())), &mut states);
+        godot_print!("Created climbing state");
+
+        self.register_state(Box::new(LedgeHang::new(context.clone())), &mut states);
+        godot_print!("Created ledge hang state");
+
+        self.register_state(Box::new(Dodging::new(context.clone())), &mut states);
+        godot_print!("Created dodging state");
+
         states
     }
 
@@ -129,4 +411,56 @@ impl FiniteStateMachine for MovementMachine {
     fn get_transitioning(&self) -> bool {
         self.transitioning
     }
+
+    fn get_state_stack(&mut self) -> &mut Vec<Self::StatesEnum> {
+        &mut self.state_stack
+    }
+
+    fn get_state_timer(&mut self) -> &mut StateTimer {
+        &mut self.state_timer
+    }
+
+    /// The input, water, breath, coyote time, the jump buffer, the dodge
+    /// cooldown, platform rotation and the locomotion blend are tracked here
+    /// so they keep running across the locomotion states
+    fn before_physics_process(&mut self, delta: f64) {
+        if !self.started {
+            return;
+        }
+
+        let delta = delta as f32;
+
+        self.update_input();
+        let input = self.context.bind().input;
+
+        motion::update_water_volume(&mut self.context.bind_mut());
+        motion::update_breath(&mut self.context, delta);
+        motion::update_jump_timers(&mut self.context.bind_mut(), &input, delta);
+        motion::update_dodge_cooldown(&mut self.context.bind_mut(), delta);
+        motion::follow_platform_rotation(&self.context.bind(), delta);
+        motion::update_locomotion_blend(&mut self.context.bind_mut(), &input, delta);
+    }
+
+    fn exit_tree(&mut self) {
+        if let Err(error) = self.stop_input_recording() {
+            godot_error!("{error}");
+        }
+    }
+
+    fn get_trace(&mut self) -> Option<&mut TransitionTrace> {
+        Some(&mut self.trace)
+    }
+
+    fn get_regions(&mut self) -> Option<&mut Vec<DynRegion>> {
+        Some(&mut self.regions)
+    }
+
+    fn can_transition(&mut self, _from: &MovementStates, to: &MovementStates) -> bool {
+        if self.guards.is_empty() {
+            return true;
+        }
+
+        let snapshot = self.get_state_snapshot();
+        self.guards.allows(to, &snapshot)
+    }
 }
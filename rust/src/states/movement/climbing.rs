@@ -0,0 +1,102 @@
+use fsm_macros::State;
+use godot::{builtin::Vector3, classes::InputEvent, obj::Gd};
+
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase},
+};
+
+use super::{
+    context::MovementContext,
+    motion::{
+        climb_axis, climb_velocity, climbable_wall, crouch_pressed, find_ledge, jump_pressed,
+        play_animation, rotate_toward_velocity,
+    },
+    movement_states::MovementStates,
+};
+
+/// Keeps the player pressed against the wall so it does not drift off
+const WALL_STICK_SPEED: f32 = 0.5;
+
+#[derive(Debug, State)]
+#[state(name = MovementStates::Climbing)]
+pub struct Climbing {
+    #[state(context)]
+    context: Gd<MovementContext>,
+    #[state(next_state)]
+    next_state: Option<MovementStates>,
+}
+
+impl State<GodotInput> for Climbing {
+    fn transitions(&self) -> Vec<MovementStates> {
+        vec![
+            MovementStates::Idle,
+            MovementStates::Jumping,
+            MovementStates::Falling,
+            MovementStates::LedgeHang,
+        ]
+    }
+
+    fn enter(&mut self) {
+        self.set_next_state(MovementStates::Climbing);
+
+        let context = self.context.bind();
+        let mut player = context.get_node(context.player_node.clone());
+        player.set_velocity(Vector3::ZERO);
+
+        play_animation(&context, &context.climb_animation_name);
+    }
+
+    fn input(&mut self, _event: Gd<InputEvent>) {}
+
+    fn process(&mut self, _delta: f32) {}
+
+    fn physics_process(&mut self, _delta: f32) {
+        let gd_context = self.context.clone();
+        let context = gd_context.bind();
+        let mut player = context.get_node(context.player_node.clone());
+
+        let input = context.input;
+        let climb = climb_axis(&input);
+
+        // NOTE: The wall ray at chest height only misses once the top of
+        // the wall is below the chest, out of reach of find_ledge(), so the
+        // ledge is grabbed while the wall is still in front of the player
+        if climb > 0. && find_ledge(&context).is_some() {
+            self.set_next_state(MovementStates::LedgeHang);
+            return;
+        }
+
+        let Some(wall) = climbable_wall(&context) else {
+            self.set_next_state(MovementStates::Falling);
+            return;
+        };
+
+        // Pushes off the wall
+        if jump_pressed(&input) {
+            player.set_velocity(wall.normal * context.movement_speed);
+            self.set_next_state(MovementStates::Jumping);
+            return;
+        }
+
+        if crouch_pressed(&input) {
+            self.set_next_state(MovementStates::Falling);
+            return;
+        }
+
+        if climb < 0. && player.is_on_floor() {
+            self.set_next_state(MovementStates::Idle);
+            return;
+        }
+
+        let velocity = climb_velocity(&context, &input, &wall) - wall.normal * WALL_STICK_SPEED;
+
+        player.set_velocity(velocity);
+        player.move_and_slide();
+        rotate_toward_velocity(&context, -wall.normal);
+    }
+
+    fn exit(&mut self) {
+        self.set_next_state(MovementStates::Climbing);
+    }
+}
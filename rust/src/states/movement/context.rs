@@ -1,13 +1,22 @@
 use godot::{
-    builtin::{GString, NodePath},
-    classes::{AnimationPlayer, CharacterBody3D, Node, Node3D},
-    obj::Gd,
-    prelude::GodotClass,
+    builtin::{GString, NodePath, Vector2, Vector3},
+    classes::{
+        AnimationPlayer, AnimationTree, Camera3D, CharacterBody3D, CollisionShape3D, Curve, Node,
+        Node3D, Resource,
+    },
+    obj::{Base, Gd},
+    prelude::{GodotClass, godot_api},
 };
 
-#[derive(Default, Debug, GodotClass)]
+use crate::common::{footsteps::FootstepTable, water_volume::WaterVolume};
+
+use super::input_recording::InputFrame;
+
+#[derive(Debug, GodotClass)]
 #[class(base=Resource, init)]
 pub struct MovementContext {
+    base: Base<Resource>,
+
     #[export]
     pub player: NodePath,
 
@@ -20,24 +29,323 @@ pub struct MovementContext {
     #[export]
     pub camera: NodePath,
 
+    #[export]
+    /// Points to the CollisionShape3D resized while crouching
+    pub collision_shape: NodePath,
+
     #[export]
     pub animation_player: GString,
 
     #[export]
     pub walking_animation_name: GString,
 
+    #[export]
+    /// Points to the AnimationTree driven by the movement states, without it
+    /// the animations are played on the AnimationPlayer
+    pub animation_tree: NodePath,
+
+    #[export]
+    /// Transition node of the AnimationTree root, the animation names of
+    /// the states are its inputs
+    pub state_transition_node: GString,
+
+    #[export(range=(0.0, 2.0))]
+    /// Seconds the transition node crossfades between states
+    pub state_crossfade_time: f32,
+
+    #[export]
+    /// Input of the transition node holding the idle/walk/run blend space
+    pub locomotion_animation_name: GString,
+
+    #[export]
+    /// Blend space node of the AnimationTree root fed with the velocity
+    pub locomotion_blend_node: GString,
+
+    #[export]
+    /// Feeds the blend space with right/forward speed instead of the speed
+    pub locomotion_blend_2d: bool,
+
+    #[export(range=(0.0, 2.0))]
+    /// Seconds the blend position takes to catch up with the velocity
+    pub locomotion_blend_time: f32,
+
+    #[export]
+    /// Moves the player on the ground by the root motion of the animations
+    /// instead of the movement speed, gravity still applies
+    pub use_root_motion: bool,
+
+    #[export]
+    /// Root motion track set on the AnimationTree or AnimationPlayer, empty
+    /// keeps the track set in the editor
+    pub root_motion_track: NodePath,
+
     #[export(range=(0.01, 400.0))]
     pub movement_speed: f32,
 
+    #[export(range=(0.0, 200.0))]
+    /// Speed gained per second towards the input direction, 0 is instant
+    pub acceleration: f32,
+
+    #[export(range=(0.0, 200.0))]
+    /// Speed lost per second without input, 0 stops instantly
+    pub deceleration: f32,
+
+    #[export(range=(0.0, 50.0))]
+    /// Radians per second the movement direction can turn, 0 turns instantly
+    pub turn_rate: f32,
+
+    #[export(range=(0.0, 1.0))]
+    /// Multiplies acceleration, deceleration and turn rate while airborne
+    pub air_control: f32,
+
+    #[export]
+    /// Scales the acceleration, sampled by current speed / target speed
+    pub acceleration_curve: Option<Gd<Curve>>,
+
+    #[export]
+    /// Scales the deceleration, sampled by current speed / movement speed
+    pub deceleration_curve: Option<Gd<Curve>>,
+
+    #[export(range=(0.0, 89.0))]
+    /// Steepest slope in degrees the player can walk on
+    pub max_slope_angle: f32,
+
+    #[export(range=(0.0, 50.0))]
+    /// Speed the player slides down slopes steeper than the max slope angle
+    pub steep_slope_slide_speed: f32,
+
+    #[export(range=(0.0, 1.0))]
+    /// Obstacles and stairs up to this height are stepped up automatically
+    pub step_height: f32,
+
+    #[export(range=(0.0, 1.0))]
+    /// How far the player is pulled to the floor when walking down slopes
+    pub floor_snap_length: f32,
+
+    #[export]
+    /// Keeps the velocity of a moving platform when jumping or walking off it
+    pub keep_platform_momentum: bool,
+
+    #[export(range=(0.01, 20.0))]
+    /// Height of a jump in meters
+    pub jump_height: f32,
+
+    #[export(range=(0.0, 10.0))]
+    /// Multiplies the project gravity while jumping and falling
+    pub gravity_scale: f32,
+
+    #[export(range=(1.0, 200.0))]
+    /// Terminal speed while falling
+    pub fall_speed: f32,
+
+    #[export(range=(0.0, 2.0))]
+    /// Seconds spent in the Landing state before returning to Idle
+    pub landing_duration: f32,
+
+    #[export(range=(0.0, 1.0))]
+    /// Seconds after leaving the floor in which a jump is still allowed
+    pub coyote_time: f32,
+
+    #[export(range=(0.0, 1.0))]
+    /// Seconds a jump pressed in the air is kept until the player lands
+    pub jump_buffer_time: f32,
+
+    #[export]
+    pub jump_animation_name: GString,
+
+    #[export]
+    pub fall_animation_name: GString,
+
+    #[export]
+    pub land_animation_name: GString,
+
+    #[export(range=(1.0, 4.0))]
+    /// Multiplies the movement speed while sprinting
+    pub sprint_speed_multiplier: f32,
+
+    #[export]
+    pub sprint_animation_name: GString,
+
+    #[export(range=(1.0, 1000.0))]
+    pub max_stamina: f32,
+
+    #[export(range=(0.0, 500.0))]
+    /// Stamina used per second of sprinting
+    pub stamina_drain: f32,
+
+    #[export(range=(0.0, 500.0))]
+    /// Stamina regained per second while idle or walking
+    pub stamina_regen: f32,
+
+    #[export]
+    pub crouch_animation_name: GString,
+
+    #[export(range=(0.1, 1.0))]
+    /// Collider height while crouching relative to the standing height
+    pub crouch_height_ratio: f32,
+
+    #[export(range=(0.0, 2.0))]
+    /// How far the camera pivot is lowered while crouching
+    pub crouch_camera_offset: f32,
+
+    #[export(range=(0.1, 1.0))]
+    /// Multiplies the movement speed while crouching
+    pub crouch_speed_multiplier: f32,
+
+    #[export(range=(0.0, 1.0))]
+    /// Multiplies how much noise the player makes while crouching
+    pub crouch_noise_multiplier: f32,
+
+    #[export]
+    pub swim_animation_name: GString,
+
+    #[export(range=(0.1, 2.0))]
+    /// Multiplies the movement speed while swimming
+    pub swim_speed_multiplier: f32,
+
+    #[export(range=(0.0, 3.0))]
+    /// Height above the player origin that has to be underwater to swim
+    pub chest_height: f32,
+
+    #[export(range=(0.0, 3.0))]
+    /// Height above the player origin that has to be underwater to use breath
+    pub head_height: f32,
+
+    #[export(range=(1.0, 600.0))]
+    /// Seconds the player can stay underwater
+    pub max_breath: f32,
+
+    #[export(range=(0.0, 100.0))]
+    /// Breath regained per second with the head above water
+    pub breath_regen: f32,
+
+    #[export]
+    /// Surfaces in this node group can be climbed, empty to only use layers
+    pub climbable_group: GString,
+
+    #[export(flags_3d_physics)]
+    /// Surfaces on these physics layers can be climbed
+    pub climbable_layers: u32,
+
+    #[export(range=(0.1, 2.0))]
+    /// Multiplies the movement speed while climbing
+    pub climb_speed_multiplier: f32,
+
+    #[export(range=(0.1, 2.0))]
+    /// How far in front of the player walls and ledges are detected
+    pub wall_reach: f32,
+
+    #[export(range=(0.0, 2.0))]
+    /// How far above the head a ledge can still be grabbed
+    pub ledge_grab_height: f32,
+
+    #[export(range=(0.05, 2.0))]
+    /// Seconds it takes to pull up onto a ledge
+    pub climb_up_duration: f32,
+
+    #[export]
+    pub climb_animation_name: GString,
+
+    #[export]
+    pub ledge_hang_animation_name: GString,
+
+    #[export]
+    pub climb_up_animation_name: GString,
+
+    #[export(range=(0.1, 20.0))]
+    /// Distance covered by a dodge
+    pub dodge_distance: f32,
+
+    #[export(range=(0.05, 2.0))]
+    /// Seconds a dodge lasts, steering is locked meanwhile
+    pub dodge_duration: f32,
+
+    #[export(range=(0.0, 10.0))]
+    /// Seconds after a dodge before the next one
+    pub dodge_cooldown: f32,
+
+    #[export(range=(0.0, 2.0))]
+    /// Seconds into the dodge the player becomes invulnerable
+    pub dodge_invulnerability_start: f32,
+
+    #[export(range=(0.0, 2.0))]
+    /// Seconds the player stays invulnerable during a dodge
+    pub dodge_invulnerability_duration: f32,
+
+    #[export]
+    pub dodge_animation_name: GString,
+
+    #[export]
+    /// Points to AudioStreamPlayer3D to play when dodging
+    pub dodge_sound: NodePath,
+
     #[export]
     /// Points to AudioStreamPlayer3D to play a footstep sound
     pub footstep: NodePath,
 
+    #[export]
+    /// Picks the footstep sounds by the floor under the player, without it
+    /// the stream of the footstep AudioStreamPlayer3D is played
+    pub footstep_sounds: Option<Gd<FootstepTable>>,
+
     pub scene_tree: Option<Gd<Node>>,
     pub pivot_node: Option<Gd<Node3D>>,
     pub player_node: Option<Gd<CharacterBody3D>>,
     pub player_scene_node: Option<Gd<Node3D>>,
     pub animator: Option<Gd<AnimationPlayer>>,
+    pub animation_tree_node: Option<Gd<AnimationTree>>,
+    pub collision_shape_node: Option<Gd<CollisionShape3D>>,
+    pub camera_node: Option<Gd<Camera3D>>,
+    /// The water the player is currently in
+    pub water_volume: Option<Gd<WaterVolume>>,
+
+    pub stamina: f32,
+    /// Set when stamina runs out, sprinting needs a new press of the
+    /// sprint action afterwards
+    pub sprint_locked: bool,
+    /// How loud the player is to NPC perception, 1.0 while standing
+    pub noise_multiplier: f32,
+    /// Movement speed multiplier of the current state, drives the blend
+    /// space when using root motion
+    pub speed_multiplier: f32,
+    /// Seconds since the player last stood on the floor
+    pub time_since_grounded: f32,
+    /// Seconds since the jump action was last pressed
+    pub time_since_jump_pressed: f32,
+    pub breath: f32,
+    /// Blend position of the locomotion blend space, relative to the
+    /// movement speed
+    pub locomotion_blend: Vector2,
+    /// Seconds until the player can dodge again
+    pub dodge_cooldown_remaining: f32,
+    /// Set during the invulnerability window of a dodge
+    pub invulnerable: bool,
+    /// Input of the current physics frame, live or from a playback
+    pub input: InputFrame,
+}
+
+#[godot_api]
+impl MovementContext {
+    #[signal]
+    /// The stamina pool changed
+    pub fn stamina_changed(stamina: f32, max_stamina: f32);
+
+    #[signal]
+    /// Sprinting used up all of the stamina
+    pub fn stamina_depleted();
+
+    #[signal]
+    /// The breath meter changed
+    pub fn breath_changed(breath: f32, max_breath: f32);
+
+    #[signal]
+    /// The player ran out of breath underwater
+    pub fn breath_depleted();
+
+    #[signal]
+    /// An input playback ran out of frames, the player ended up at
+    /// `position`
+    pub fn input_playback_finished(position: Vector3);
 }
 
 impl MovementContext {
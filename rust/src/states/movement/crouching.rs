@@ -0,0 +1,201 @@
+use fsm_macros::State;
+use godot::{
+    builtin::{Array, Rid, Vector3},
+    classes::{CapsuleShape3D, InputEvent, PhysicsShapeQueryParameters3D},
+    obj::{Gd, NewGd},
+};
+
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase},
+};
+
+use super::{
+    context::MovementContext,
+    motion::{
+        accelerate, apply_root_motion, crouch_pressed, gravity, input_velocity, play_animation,
+        regen_stamina, rotate_toward_velocity, step_up,
+    },
+    movement_states::MovementStates,
+};
+
+/// Lifts the ceiling check off the floor so touching it does not count as a hit
+const CEILING_CHECK_MARGIN: f32 = 0.05;
+
+#[derive(Debug, State)]
+#[state(name = MovementStates::Crouching)]
+pub struct Crouching {
+    #[state(context)]
+    context: Gd<MovementContext>,
+    #[state(next_state)]
+    next_state: Option<MovementStates>,
+    velocity: Vector3,
+    standing_height: f32,
+    standing_shape_position: Vector3,
+    standing_pivot_position: Vector3,
+}
+
+impl Crouching {
+    fn capsule(context: &MovementContext) -> Option<Gd<CapsuleShape3D>> {
+        let collision_shape = context.get_node(context.collision_shape_node.clone());
+
+        collision_shape
+            .get_shape()
+            .and_then(|shape| shape.try_cast::<CapsuleShape3D>().ok())
+    }
+
+    fn crouch(&mut self) {
+        let gd_context = self.context.clone();
+        let context = gd_context.bind();
+
+        let mut collision_shape = context.get_node(context.collision_shape_node.clone());
+        let mut pivot = context.get_node(context.pivot_node.clone());
+
+        self.standing_shape_position = collision_shape.get_position();
+        self.standing_pivot_position = pivot.get_position();
+
+        if let Some(mut capsule) = Self::capsule(&context) {
+            self.standing_height = capsule.get_height();
+
+            let crouch_height = self.standing_height * context.crouch_height_ratio;
+            let lowered_by = (self.standing_height - crouch_height) / 2.;
+
+            capsule.set_height(crouch_height);
+            collision_shape.set_position(self.standing_shape_position - Vector3::UP * lowered_by);
+        }
+
+        pivot.set_position(
+            self.standing_pivot_position - Vector3::UP * context.crouch_camera_offset,
+        );
+    }
+
+    fn stand(&mut self) {
+        let context = self.context.bind();
+
+        let mut collision_shape = context.get_node(context.collision_shape_node.clone());
+        let mut pivot = context.get_node(context.pivot_node.clone());
+
+        if let Some(mut capsule) = Self::capsule(&context) {
+            capsule.set_height(self.standing_height);
+        }
+
+        collision_shape.set_position(self.standing_shape_position);
+        pivot.set_position(self.standing_pivot_position);
+    }
+
+    /// Casts the crouched collider up to the standing height, standing up
+    /// is refused when anything is in the way
+    fn has_ceiling(&self, context: &MovementContext) -> bool {
+        let player = context.get_node(context.player_node.clone());
+        let collision_shape = context.get_node(context.collision_shape_node.clone());
+
+        let Some(capsule) = Self::capsule(context) else {
+            return false;
+        };
+
+        let Some(mut space_state) = player
+            .get_world_3d()
+            .and_then(|mut world_3d| world_3d.get_direct_space_state())
+        else {
+            return false;
+        };
+
+        let mut transform = collision_shape.get_global_transform();
+        transform.origin += Vector3::UP * CEILING_CHECK_MARGIN;
+
+        let mut excludes: Array<Rid> = Array::new();
+        excludes.push(player.get_rid());
+
+        let mut query = PhysicsShapeQueryParameters3D::new_gd();
+        query.set_shape(&capsule);
+        query.set_transform(transform);
+        query.set_motion(Vector3::UP * (self.standing_height - capsule.get_height()));
+        query.set_exclude(&excludes);
+
+        let motion = space_state.cast_motion(&query);
+
+        motion
+            .get(0)
+            .is_some_and(|safe_fraction| safe_fraction < 1.)
+    }
+}
+
+impl State<GodotInput> for Crouching {
+    fn transitions(&self) -> Vec<MovementStates> {
+        vec![MovementStates::Idle, MovementStates::Walking]
+    }
+
+    fn enter(&mut self) {
+        self.set_next_state(MovementStates::Crouching);
+        self.crouch();
+
+        let mut context = self.context.bind_mut();
+        self.velocity = context.get_node(context.player_node.clone()).get_velocity();
+        context.noise_multiplier = context.crouch_noise_multiplier;
+        context.speed_multiplier = context.crouch_speed_multiplier;
+        play_animation(&context, &context.crouch_animation_name);
+    }
+
+    fn input(&mut self, _event: Gd<InputEvent>) {}
+
+    fn process(&mut self, _delta: f32) {}
+
+    fn physics_process(&mut self, delta: f32) {
+        regen_stamina(&mut self.context, delta);
+
+        let gd_context = self.context.clone();
+        let context = gd_context.bind();
+        let mut player = context.get_node(context.player_node.clone());
+
+        let input = context.input;
+        let horizontal = input_velocity(&context, &input) * context.crouch_speed_multiplier;
+
+        if !crouch_pressed(&input) && !self.has_ceiling(&context) {
+            if horizontal == Vector3::ZERO {
+                self.set_next_state(MovementStates::Idle);
+            } else {
+                self.set_next_state(MovementStates::Walking);
+            }
+
+            return;
+        }
+
+        // NOTE: Stays crouched when walking off a ledge so the collider
+        // never grows into a ceiling
+        let airborne = !player.is_on_floor();
+        self.velocity = accelerate(&context, self.velocity, horizontal, delta, airborne);
+        self.velocity.y = if airborne {
+            (self.velocity.y + gravity(&context) * delta).max(-context.fall_speed)
+        } else {
+            gravity(&context) * delta
+        };
+
+        let velocity = if airborne {
+            self.velocity
+        } else {
+            apply_root_motion(&context, self.velocity, delta)
+        };
+
+        player.set_velocity(velocity);
+        player.move_and_slide();
+        rotate_toward_velocity(&context, self.velocity);
+
+        if !airborne {
+            step_up(&context, &mut player, self.velocity * delta);
+        }
+
+        // Picks up the platform velocity added when leaving a moving platform
+        if !player.is_on_floor() {
+            self.velocity = player.get_velocity();
+        }
+    }
+
+    fn exit(&mut self) {
+        self.stand();
+        self.set_next_state(MovementStates::Crouching);
+
+        let mut context = self.context.bind_mut();
+        context.noise_multiplier = 1.;
+        context.speed_multiplier = 1.;
+    }
+}
@@ -0,0 +1,106 @@
+use fsm_macros::State;
+use godot::{builtin::Vector3, classes::InputEvent, obj::Gd};
+
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase},
+};
+
+use super::{
+    context::MovementContext,
+    motion::{
+        apply_root_motion, forward_direction, gravity, input_velocity, play_animation, play_sound,
+        rotate_toward_velocity,
+    },
+    movement_states::MovementStates,
+};
+
+#[derive(Debug, State)]
+#[state(name = MovementStates::Dodging)]
+pub struct Dodging {
+    #[state(context)]
+    context: Gd<MovementContext>,
+    #[state(next_state)]
+    next_state: Option<MovementStates>,
+    velocity: Vector3,
+    elapsed: f32,
+}
+
+impl State<GodotInput> for Dodging {
+    fn transitions(&self) -> Vec<MovementStates> {
+        vec![
+            MovementStates::Idle,
+            MovementStates::Walking,
+            MovementStates::Falling,
+        ]
+    }
+
+    fn enter(&mut self) {
+        self.set_next_state(MovementStates::Dodging);
+        self.elapsed = 0.;
+
+        let context = self.context.bind();
+        let input = context.input;
+
+        // NOTE: Steering is locked, the direction is only read once
+        let input_direction = input_velocity(&context, &input);
+        let direction = if input_direction == Vector3::ZERO {
+            forward_direction(&context)
+        } else {
+            input_direction.normalized()
+        };
+
+        self.velocity = direction * context.dodge_distance / context.dodge_duration;
+
+        play_animation(&context, &context.dodge_animation_name);
+        play_sound(&context, &context.dodge_sound);
+    }
+
+    fn input(&mut self, _event: Gd<InputEvent>) {}
+
+    fn process(&mut self, _delta: f32) {}
+
+    fn physics_process(&mut self, delta: f32) {
+        self.elapsed += delta;
+
+        let mut gd_context = self.context.clone();
+        let mut context = gd_context.bind_mut();
+        let mut player = context.get_node(context.player_node.clone());
+
+        let window_start = context.dodge_invulnerability_start;
+        let window_end = window_start + context.dodge_invulnerability_duration;
+        context.invulnerable = (window_start..window_end).contains(&self.elapsed);
+
+        self.velocity.y = (self.velocity.y + gravity(&context) * delta).max(-context.fall_speed);
+
+        player.set_velocity(apply_root_motion(&context, self.velocity, delta));
+        player.move_and_slide();
+        rotate_toward_velocity(&context, self.velocity);
+
+        if player.is_on_floor() {
+            self.velocity.y = 0.;
+        }
+
+        // NOTE: Checked after moving so the last frame still covers its
+        // share of the dodge distance
+        if self.elapsed >= context.dodge_duration {
+            let input = context.input;
+
+            if !player.is_on_floor() {
+                self.set_next_state(MovementStates::Falling);
+            } else if input_velocity(&context, &input) == Vector3::ZERO {
+                self.set_next_state(MovementStates::Idle);
+            } else {
+                self.set_next_state(MovementStates::Walking);
+            }
+        }
+    }
+
+    fn exit(&mut self) {
+        self.set_next_state(MovementStates::Dodging);
+
+        let mut context = self.context.bind_mut();
+        context.invulnerable = false;
+        context.dodge_cooldown_remaining = context.dodge_cooldown;
+    }
+}
@@ -0,0 +1,101 @@
+use fsm_macros::State;
+use godot::{builtin::Vector3, classes::InputEvent, obj::Gd};
+
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase},
+};
+
+use super::{
+    context::MovementContext,
+    motion::{
+        accelerate, can_climb, find_ledge, gravity, input_velocity, is_submerged, play_animation,
+        should_jump, slide_down_steep_slope,
+    },
+    movement_states::MovementStates,
+};
+
+#[derive(Debug, State)]
+#[state(name = MovementStates::Falling)]
+pub struct Falling {
+    #[state(context)]
+    context: Gd<MovementContext>,
+    #[state(next_state)]
+    next_state: Option<MovementStates>,
+    velocity: Vector3,
+}
+
+impl State<GodotInput> for Falling {
+    fn transitions(&self) -> Vec<MovementStates> {
+        vec![
+            MovementStates::Jumping,
+            MovementStates::Landing,
+            MovementStates::Swimming,
+            MovementStates::Climbing,
+            MovementStates::LedgeHang,
+        ]
+    }
+
+    fn enter(&mut self) {
+        self.set_next_state(MovementStates::Falling);
+
+        let context = self.context.bind();
+        let player = context.get_node(context.player_node.clone());
+
+        // Drops the upward speed of a jump that hit a ceiling
+        self.velocity = player.get_velocity();
+        self.velocity.y = self.velocity.y.min(0.);
+
+        play_animation(&context, &context.fall_animation_name);
+    }
+
+    fn input(&mut self, _event: Gd<InputEvent>) {}
+
+    fn process(&mut self, _delta: f32) {}
+
+    fn physics_process(&mut self, delta: f32) {
+        let gd_context = self.context.clone();
+        let context = gd_context.bind();
+        let mut player = context.get_node(context.player_node.clone());
+
+        if is_submerged(&context) {
+            self.set_next_state(MovementStates::Swimming);
+            return;
+        }
+
+        // Coyote time, the player left the floor without jumping a moment ago
+        if should_jump(&context) {
+            self.set_next_state(MovementStates::Jumping);
+            return;
+        }
+
+        let input = context.input;
+        let horizontal = input_velocity(&context, &input);
+
+        if can_climb(&context, &input) {
+            self.set_next_state(MovementStates::Climbing);
+            return;
+        }
+
+        // Grabs ledges the player moves towards
+        if find_ledge(&context).is_some_and(|ledge| horizontal.dot(ledge.normal) < 0.) {
+            self.set_next_state(MovementStates::LedgeHang);
+            return;
+        }
+
+        self.velocity = accelerate(&context, self.velocity, horizontal, delta, true);
+        self.velocity.y = (self.velocity.y + gravity(&context) * delta).max(-context.fall_speed);
+        self.velocity = slide_down_steep_slope(&context, &player, self.velocity);
+
+        player.set_velocity(self.velocity);
+        player.move_and_slide();
+
+        if player.is_on_floor() {
+            self.set_next_state(MovementStates::Landing);
+        }
+    }
+
+    fn exit(&mut self) {
+        self.set_next_state(MovementStates::Falling);
+    }
+}
@@ -1,73 +1,98 @@
-use std::sync::LazyLock;
-
-use godot::{builtin::Vector2, classes::Input, obj::Gd};
-
-use crate::{actions::Actions, common::states::State};
-
-use super::{context::MovementContext, movement_states::MovementStates};
-
-// TODO: Figure out a better way to do this so that I don't have to
-// duplicate this object in both Idle and Walking states
-static ACTIONS: LazyLock<Actions> = LazyLock::new(Actions::default);
-
-#[derive(Debug)]
+use fsm_macros::State;
+use godot::{
+    builtin::{GString, Vector2, Vector3},
+    obj::Gd,
+};
+
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase},
+};
+
+use super::{
+    context::MovementContext,
+    motion::{
+        accelerate, apply_root_motion, can_dodge, crouch_pressed, gravity, is_submerged,
+        play_locomotion, regen_stamina, should_jump,
+    },
+    movement_states::MovementStates,
+};
+
+#[derive(Debug, State)]
+#[state(name = MovementStates::Idle)]
 pub struct Idle {
-    #[allow(unused)]
+    #[state(context)]
     context: Gd<MovementContext>,
+    #[state(next_state)]
     next_state: Option<MovementStates>,
 }
 
-impl State for Idle {
-    type StatesEnum = MovementStates;
-    type Context = Gd<MovementContext>;
-
-    fn new(context: Self::Context) -> Self {
-        Idle {
-            context,
-            next_state: None,
-        }
+impl State<GodotInput> for Idle {
+    fn transitions(&self) -> Vec<MovementStates> {
+        vec![
+            MovementStates::Walking,
+            MovementStates::Crouching,
+            MovementStates::Jumping,
+            MovementStates::Falling,
+            MovementStates::Swimming,
+            MovementStates::Dodging,
+        ]
     }
 
-    fn get_state_name(&self) -> Self::StatesEnum {
-        MovementStates::Idle
-    }
+    fn enter(&mut self) {
+        self.set_next_state(MovementStates::Idle);
 
-    fn set_next_state(&mut self, state: Self::StatesEnum) {
-        self.next_state = Some(state);
+        let context = self.context.bind();
+        play_locomotion(&context, &GString::new());
     }
 
-    fn get_next_state(&mut self) -> Option<Self::StatesEnum> {
-        self.next_state.clone()
-    }
+    fn input(&mut self, _event: Gd<godot::classes::InputEvent>) {}
+
+    fn process(&mut self, _delta: f32) {}
+
+    fn physics_process(&mut self, delta: f32) {
+        regen_stamina(&mut self.context, delta);
 
-    fn enter(&mut self) {
         let gd_context = self.context.clone();
         let context = gd_context.bind();
-        let mut animator = context.get_node(context.animator.clone());
+        let mut player = context.get_node(context.player_node.clone());
 
-        self.set_next_state(MovementStates::Idle);
+        if is_submerged(&context) {
+            self.set_next_state(MovementStates::Swimming);
+            return;
+        }
 
-        animator.stop();
-    }
+        let input = context.input;
+        if can_dodge(&context, &input) {
+            self.set_next_state(MovementStates::Dodging);
+            return;
+        }
 
-    fn input(&mut self, _event: Gd<godot::classes::InputEvent>) {
-        let input = Input::singleton();
+        if should_jump(&context) {
+            self.set_next_state(MovementStates::Jumping);
+            return;
+        }
 
-        let movement_vector = input.get_vector(
-            ACTIONS.right,
-            ACTIONS.left,
-            ACTIONS.backward,
-            ACTIONS.forward,
-        );
+        if player.is_on_floor() && crouch_pressed(&input) {
+            self.set_next_state(MovementStates::Crouching);
+            return;
+        }
 
-        if movement_vector != Vector2::ZERO {
+        if input.movement_vector() != Vector2::ZERO {
             self.set_next_state(MovementStates::Walking);
         }
-    }
 
-    fn process(&mut self, _delta: f32) {}
+        // Slows down to a stop and keeps the floor contact up to date
+        let mut velocity = accelerate(&context, player.get_velocity(), Vector3::ZERO, delta, false);
+        velocity.y = gravity(&context) * delta;
 
-    fn physics_process(&mut self, _delta: f32) {}
+        player.set_velocity(apply_root_motion(&context, velocity, delta));
+        player.move_and_slide();
+
+        if !player.is_on_floor() {
+            self.set_next_state(MovementStates::Falling);
+        }
+    }
 
     fn exit(&mut self) {
         self.set_next_state(MovementStates::Idle);
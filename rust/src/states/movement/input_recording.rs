@@ -0,0 +1,223 @@
+use godot::{
+    builtin::{Vector2, Vector3},
+    classes::{FileAccess, Input, file_access::ModeFlags},
+    global::Error as GodotError,
+    obj::Gd,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::actions::ACTIONS;
+
+use super::context::MovementContext;
+
+#[derive(Error, Debug)]
+pub enum InputRecordingError {
+    #[error("The input recording file could not be opened: {0:?}")]
+    Open(GodotError),
+    #[error("The input recording could not be serialized")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Input state the movement states read, captured once per physics frame
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InputFrame {
+    /// Movement actions with their analog magnitude, x is left and y forward
+    pub movement: [f32; 2],
+    /// Forward minus backward movement action strength
+    pub forward_axis: f32,
+    /// Right minus left movement action strength
+    pub strafe_axis: f32,
+    pub jump_pressed: bool,
+    pub jump_held: bool,
+    pub dodge_pressed: bool,
+    pub crouch_held: bool,
+    pub sprint_held: bool,
+    /// Global yaw of the camera pivot, the movement is relative to it
+    pub view_yaw: f32,
+    /// How far the camera looks up, -1 is straight down and 1 straight up
+    pub view_pitch: f32,
+}
+
+impl InputFrame {
+    pub fn capture(input: &Gd<Input>, context: &MovementContext) -> Self {
+        let movement = ACTIONS.movement_vector(input);
+        let pivot = context.get_node(context.pivot_node.clone());
+        let camera = context.get_node(context.camera_node.clone());
+
+        Self {
+            movement: [movement.x, movement.y],
+            forward_axis: input.get_axis(ACTIONS.backward, ACTIONS.forward),
+            strafe_axis: input.get_axis(ACTIONS.left, ACTIONS.right),
+            jump_pressed: input.is_action_just_pressed(ACTIONS.jump),
+            jump_held: input.is_action_pressed(ACTIONS.jump),
+            dodge_pressed: input.is_action_just_pressed(ACTIONS.dodge),
+            crouch_held: input.is_action_pressed(ACTIONS.crouch),
+            sprint_held: input.is_action_pressed(ACTIONS.sprint),
+            view_yaw: pivot.get_global_rotation().y,
+            view_pitch: -camera.get_global_transform().basis.col_c().y,
+        }
+    }
+
+    pub fn movement_vector(&self) -> Vector2 {
+        Vector2::new(self.movement[0], self.movement[1])
+    }
+}
+
+/// The input of every physics frame from the point the recording started,
+/// along with the player state it started from
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct InputRecording {
+    pub physics_ticks_per_second: i32,
+    pub start_position: [f32; 3],
+    pub start_velocity: [f32; 3],
+    /// Global yaw of the player model
+    pub start_facing: f32,
+    /// Where the player ended up, compared against after a playback
+    pub final_position: Option<[f32; 3]>,
+    pub frames: Vec<InputFrame>,
+}
+
+impl InputRecording {
+    pub fn save(&self, path: &str) -> Result<(), InputRecordingError> {
+        let json = serde_json::to_string(self)?;
+        let Some(mut file) = FileAccess::open(path, ModeFlags::WRITE) else {
+            return Err(InputRecordingError::Open(FileAccess::get_open_error()));
+        };
+
+        file.store_string(&json);
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Self, InputRecordingError> {
+        if !FileAccess::file_exists(path) {
+            return Err(InputRecordingError::Open(GodotError::ERR_FILE_NOT_FOUND));
+        }
+
+        let json = FileAccess::get_file_as_string(path);
+        Ok(serde_json::from_str(&json.to_string())?)
+    }
+}
+
+/// Where the movement states get their input from
+#[derive(Debug, Default)]
+pub enum InputSource {
+    #[default]
+    Live,
+    /// Live input that is also appended to the recording, saved to `path`
+    /// once the recording stops
+    Recording {
+        recording: InputRecording,
+        path: String,
+    },
+    /// Frames of a recording fed back in place of the live input
+    Playback {
+        recording: InputRecording,
+        frame: usize,
+    },
+}
+
+impl InputSource {
+    /// Input of the next physics frame, None once a playback ran out of
+    /// frames
+    pub fn next_frame(&mut self, live: InputFrame) -> Option<InputFrame> {
+        match self {
+            Self::Live => Some(live),
+            Self::Recording { recording, .. } => {
+                recording.frames.push(live);
+                Some(live)
+            }
+            Self::Playback { recording, frame } => {
+                let next = recording.frames.get(*frame).copied();
+                *frame += 1;
+                next
+            }
+        }
+    }
+}
+
+pub fn to_array(vector: Vector3) -> [f32; 3] {
+    [vector.x, vector.y, vector.z]
+}
+
+pub fn from_array(array: [f32; 3]) -> Vector3 {
+    Vector3::new(array[0], array[1], array[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InputFrame, InputRecording, InputSource};
+
+    fn frame(forward_axis: f32) -> InputFrame {
+        InputFrame {
+            movement: [0., forward_axis],
+            forward_axis,
+            jump_pressed: forward_axis > 0.5,
+            view_yaw: 1.25,
+            view_pitch: -0.1,
+            ..InputFrame::default()
+        }
+    }
+
+    #[test]
+    fn live_source_passes_the_live_frame() {
+        let mut source = InputSource::Live;
+
+        assert_eq!(source.next_frame(frame(1.)), Some(frame(1.)));
+    }
+
+    #[test]
+    fn recording_source_appends_the_live_frames() {
+        let mut source = InputSource::Recording {
+            recording: InputRecording::default(),
+            path: "user://recording.json".to_string(),
+        };
+
+        assert_eq!(source.next_frame(frame(0.25)), Some(frame(0.25)));
+        assert_eq!(source.next_frame(frame(1.)), Some(frame(1.)));
+
+        let InputSource::Recording { recording, .. } = source else {
+            panic!("the source should still be recording");
+        };
+        assert_eq!(recording.frames, [frame(0.25), frame(1.)]);
+    }
+
+    #[test]
+    fn playback_source_replays_the_recorded_frames() {
+        let recording = InputRecording {
+            frames: vec![frame(0.25), frame(1.)],
+            ..InputRecording::default()
+        };
+        let mut source = InputSource::Playback {
+            recording,
+            frame: 0,
+        };
+
+        // NOTE: The live input is ignored during a playback
+        assert_eq!(source.next_frame(frame(0.)), Some(frame(0.25)));
+        assert_eq!(source.next_frame(frame(0.)), Some(frame(1.)));
+        assert_eq!(source.next_frame(frame(0.)), None);
+    }
+
+    #[test]
+    fn recording_survives_a_json_round_trip() {
+        let recording = InputRecording {
+            physics_ticks_per_second: 60,
+            start_position: [1.5, 0., -3.25],
+            start_velocity: [0., -0.5, 0.],
+            start_facing: 0.75,
+            final_position: Some([2., 0., -1.]),
+            frames: vec![frame(0.25), frame(1.), frame(-0.3)],
+        };
+
+        let json = serde_json::to_string(&recording).unwrap();
+        let loaded: InputRecording = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.physics_ticks_per_second, 60);
+        assert_eq!(loaded.start_position, recording.start_position);
+        assert_eq!(loaded.start_velocity, recording.start_velocity);
+        assert_eq!(loaded.start_facing, recording.start_facing);
+        assert_eq!(loaded.final_position, recording.final_position);
+        assert_eq!(loaded.frames, recording.frames);
+    }
+}
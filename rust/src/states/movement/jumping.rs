@@ -0,0 +1,80 @@
+use fsm_macros::State;
+use godot::{builtin::Vector3, classes::InputEvent, obj::Gd};
+
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase},
+};
+
+use super::{
+    context::MovementContext,
+    motion::{
+        accelerate, can_climb, consume_jump, gravity, input_velocity, jump_velocity, play_animation,
+    },
+    movement_states::MovementStates,
+};
+
+#[derive(Debug, State)]
+#[state(name = MovementStates::Jumping)]
+pub struct Jumping {
+    #[state(context)]
+    context: Gd<MovementContext>,
+    #[state(next_state)]
+    next_state: Option<MovementStates>,
+    velocity: Vector3,
+}
+
+impl State<GodotInput> for Jumping {
+    fn transitions(&self) -> Vec<MovementStates> {
+        vec![MovementStates::Falling, MovementStates::Climbing]
+    }
+
+    fn enter(&mut self) {
+        self.set_next_state(MovementStates::Jumping);
+        consume_jump(&mut self.context.bind_mut());
+
+        let context = self.context.bind();
+        let player = context.get_node(context.player_node.clone());
+
+        self.velocity = player.get_velocity();
+        self.velocity.y = jump_velocity(&context);
+
+        play_animation(&context, &context.jump_animation_name);
+    }
+
+    fn input(&mut self, _event: Gd<InputEvent>) {}
+
+    fn process(&mut self, _delta: f32) {}
+
+    fn physics_process(&mut self, delta: f32) {
+        let gd_context = self.context.clone();
+        let context = gd_context.bind();
+        let mut player = context.get_node(context.player_node.clone());
+
+        let input = context.input;
+        let horizontal = input_velocity(&context, &input);
+
+        if can_climb(&context, &input) {
+            self.set_next_state(MovementStates::Climbing);
+            return;
+        }
+
+        self.velocity = accelerate(&context, self.velocity, horizontal, delta, true);
+        self.velocity.y += gravity(&context) * delta;
+
+        player.set_velocity(self.velocity);
+        player.move_and_slide();
+
+        // Picks up the platform velocity added when leaving a moving platform
+        self.velocity = player.get_velocity();
+
+        // NOTE: Hitting a ceiling ends the jump early
+        if self.velocity.y <= 0. || player.is_on_ceiling() {
+            self.set_next_state(MovementStates::Falling);
+        }
+    }
+
+    fn exit(&mut self) {
+        self.set_next_state(MovementStates::Jumping);
+    }
+}
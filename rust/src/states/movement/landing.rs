@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use fsm_macros::State;
+use godot::{builtin::Vector3, classes::InputEvent, obj::Gd};
+
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase},
+};
+
+use super::{
+    context::MovementContext,
+    motion::{accelerate, apply_root_motion, gravity, input_velocity, play_animation, should_jump},
+    movement_states::MovementStates,
+};
+
+#[derive(Debug, State)]
+#[state(name = MovementStates::Landing)]
+pub struct Landing {
+    #[state(context)]
+    context: Gd<MovementContext>,
+    #[state(next_state)]
+    next_state: Option<MovementStates>,
+}
+
+impl State<GodotInput> for Landing {
+    fn transitions(&self) -> Vec<MovementStates> {
+        vec![
+            MovementStates::Walking,
+            MovementStates::Jumping,
+            MovementStates::Falling,
+        ]
+    }
+
+    fn after(&self) -> Option<(Duration, MovementStates)> {
+        let duration = self.context.bind().landing_duration.max(0.);
+
+        Some((Duration::from_secs_f32(duration), MovementStates::Idle))
+    }
+
+    fn enter(&mut self) {
+        self.set_next_state(MovementStates::Landing);
+
+        let context = self.context.bind();
+        let mut player = context.get_node(context.player_node.clone());
+
+        player.set_velocity(Vector3::ZERO);
+        play_animation(&context, &context.land_animation_name);
+    }
+
+    fn input(&mut self, _event: Gd<InputEvent>) {}
+
+    fn process(&mut self, _delta: f32) {}
+
+    fn physics_process(&mut self, delta: f32) {
+        let gd_context = self.context.clone();
+        let context = gd_context.bind();
+        let mut player = context.get_node(context.player_node.clone());
+
+        let input = context.input;
+
+        if should_jump(&context) {
+            self.set_next_state(MovementStates::Jumping);
+            return;
+        }
+
+        if input_velocity(&context, &input) != Vector3::ZERO {
+            self.set_next_state(MovementStates::Walking);
+            return;
+        }
+
+        // Keeps the player on the floor, move_and_slide() also carries it
+        // along with a moving platform
+        let mut velocity = accelerate(&context, player.get_velocity(), Vector3::ZERO, delta, false);
+        velocity.y = gravity(&context) * delta;
+
+        player.set_velocity(apply_root_motion(&context, velocity, delta));
+        player.move_and_slide();
+
+        if !player.is_on_floor() {
+            self.set_next_state(MovementStates::Falling);
+        }
+    }
+
+    fn exit(&mut self) {
+        self.set_next_state(MovementStates::Landing);
+    }
+}
@@ -0,0 +1,140 @@
+use fsm_macros::State;
+use godot::{builtin::Vector3, classes::InputEvent, obj::Gd};
+
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase},
+};
+
+use super::{
+    context::MovementContext,
+    motion::{
+        WallHit, climb_axis, crouch_pressed, find_ledge, has_room_to_stand, jump_pressed,
+        ledge_stand_position, play_animation, rotate_toward_velocity,
+    },
+    movement_states::MovementStates,
+};
+
+/// Pull up onto a ledge, moves up over the first half and onto the ledge
+/// over the second half
+#[derive(Debug)]
+struct ClimbUp {
+    from: Vector3,
+    to: Vector3,
+    elapsed: f32,
+}
+
+#[derive(Debug, State)]
+#[state(name = MovementStates::LedgeHang)]
+pub struct LedgeHang {
+    #[state(context)]
+    context: Gd<MovementContext>,
+    #[state(next_state)]
+    next_state: Option<MovementStates>,
+    ledge: Option<WallHit>,
+    climb_up: Option<ClimbUp>,
+}
+
+impl LedgeHang {
+    /// Moves the player along the climb up, returns true once it stands on the ledge
+    fn update_climb_up(&mut self, context: &MovementContext, delta: f32) -> bool {
+        let Some(climb_up) = self.climb_up.as_mut() else {
+            return false;
+        };
+
+        climb_up.elapsed += delta;
+        let progress = (climb_up.elapsed / context.climb_up_duration).min(1.);
+        let rise = (progress * 2.).min(1.);
+        let advance = (progress * 2. - 1.).max(0.);
+
+        let offset = climb_up.to - climb_up.from;
+        let position = climb_up.from
+            + Vector3::UP * offset.y * rise
+            + Vector3::new(offset.x, 0., offset.z) * advance;
+
+        let mut player = context.get_node(context.player_node.clone());
+        player.set_global_position(position);
+
+        progress >= 1.
+    }
+}
+
+impl State<GodotInput> for LedgeHang {
+    fn transitions(&self) -> Vec<MovementStates> {
+        vec![MovementStates::Idle, MovementStates::Falling]
+    }
+
+    fn enter(&mut self) {
+        self.set_next_state(MovementStates::LedgeHang);
+        self.climb_up = None;
+
+        let context = self.context.bind();
+        let mut player = context.get_node(context.player_node.clone());
+
+        self.ledge = find_ledge(&context);
+        let Some(ledge) = self.ledge else {
+            return;
+        };
+
+        // Hangs with the head at the height of the ledge
+        let mut position = player.get_global_position();
+        position.y = ledge.position.y - context.head_height;
+
+        player.set_global_position(position);
+        player.set_velocity(Vector3::ZERO);
+        rotate_toward_velocity(&context, -ledge.normal);
+
+        play_animation(&context, &context.ledge_hang_animation_name);
+    }
+
+    fn input(&mut self, _event: Gd<InputEvent>) {}
+
+    fn process(&mut self, _delta: f32) {}
+
+    fn physics_process(&mut self, delta: f32) {
+        let gd_context = self.context.clone();
+        let context = gd_context.bind();
+
+        let Some(ledge) = self.ledge else {
+            self.set_next_state(MovementStates::Falling);
+            return;
+        };
+
+        if self.climb_up.is_some() {
+            if self.update_climb_up(&context, delta) {
+                self.set_next_state(MovementStates::Idle);
+            }
+
+            return;
+        }
+
+        let input = context.input;
+        let climb = climb_axis(&input);
+
+        if crouch_pressed(&input) || climb < 0. {
+            self.set_next_state(MovementStates::Falling);
+            return;
+        }
+
+        if climb > 0. || jump_pressed(&input) {
+            let to = ledge_stand_position(&context, &ledge);
+
+            // NOTE: Keeps hanging when the ledge is too low to stand on
+            if has_room_to_stand(&context, to) {
+                let player = context.get_node(context.player_node.clone());
+                self.climb_up = Some(ClimbUp {
+                    from: player.get_global_position(),
+                    to,
+                    elapsed: 0.,
+                });
+
+                play_animation(&context, &context.climb_up_animation_name);
+            }
+        }
+    }
+
+    fn exit(&mut self) {
+        self.set_next_state(MovementStates::LedgeHang);
+        self.climb_up = None;
+    }
+}
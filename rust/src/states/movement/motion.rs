@@ -0,0 +1,736 @@
+use std::f32::consts::PI;
+
+use godot::{
+    builtin::{Array, Basis, GString, NodePath, Rid, StringName, Transform3D, Vector2, Vector3},
+    classes::{
+        AnimationMixer, AnimationNodeBlendTree, AnimationNodeTransition, AudioStreamPlayer3D,
+        CapsuleShape3D, CharacterBody3D, CollisionObject3D, Curve, KinematicCollision3D, Node,
+        Object, PhysicsRayQueryParameters3D, PhysicsShapeQueryParameters3D,
+        animation_mixer::AnimationCallbackModeProcess, character_body_3d::PlatformOnLeave,
+    },
+    obj::{Gd, NewGd, WithUserSignals},
+    prelude::ToGodot,
+};
+
+use crate::{
+    common::water_volume::{WATER_VOLUME_GROUP, WaterVolume},
+    movement::project_gravity,
+};
+
+use super::{context::MovementContext, input_recording::InputFrame};
+
+/// How far below the ledge top the wall is probed for its normal
+const LEDGE_PROBE_DEPTH: f32 = 0.1;
+
+/// Lifts the room check off the ledge so touching it does not count as a hit
+const STAND_CHECK_MARGIN: f32 = 0.05;
+
+/// A surface in front of the player
+#[derive(Debug, Clone, Copy)]
+pub struct WallHit {
+    pub position: Vector3,
+    pub normal: Vector3,
+}
+
+/// Camera relative velocity from the movement actions, y is always 0. A
+/// partially tilted stick gives a proportionally lower speed
+pub fn input_velocity(context: &MovementContext, input: &InputFrame) -> Vector3 {
+    let movement_vector = input.movement_vector().rotated(-input.view_yaw);
+
+    Vector3::new(movement_vector.x, 0., movement_vector.y) * context.movement_speed
+}
+
+/// Steers the horizontal part of `velocity` towards `target` using the
+/// acceleration, deceleration and turn rate of the context, y is kept
+pub fn accelerate(
+    context: &MovementContext,
+    velocity: Vector3,
+    target: Vector3,
+    delta: f32,
+    airborne: bool,
+) -> Vector3 {
+    let control = if airborne { context.air_control } else { 1. };
+    let current = Vector3::new(velocity.x, 0., velocity.z);
+    let target = Vector3::new(target.x, 0., target.z);
+    let speed = current.length();
+    let target_speed = target.length();
+
+    let deceleration_scale = curve_factor(
+        &context.deceleration_curve,
+        speed / context.movement_speed.max(f32::EPSILON),
+    ) * control
+        * delta;
+
+    let horizontal = if target_speed <= f32::EPSILON {
+        step_toward(
+            current,
+            Vector3::ZERO,
+            context.deceleration,
+            deceleration_scale,
+        )
+    } else if speed <= f32::EPSILON {
+        let scale = curve_factor(&context.acceleration_curve, 0.) * control * delta;
+        step_toward(current, target, context.acceleration, scale)
+    } else {
+        let current_direction = current / speed;
+        let target_direction = target / target_speed;
+        let angle = current_direction.angle_to(target_direction);
+        let max_turn = context.turn_rate * control * delta;
+
+        // NOTE: Reversing brakes first, there is no side to turn towards
+        let (direction, target_speed) = if context.turn_rate <= 0. || angle <= max_turn {
+            (target_direction, target_speed)
+        } else if angle >= PI - 0.01 {
+            (current_direction, 0.)
+        } else {
+            let direction = current_direction.slerp(target_direction, max_turn / angle);
+            (direction, target_speed)
+        };
+
+        let (rate, scale) = if target_speed >= speed {
+            let ratio = speed / target_speed;
+            let scale = curve_factor(&context.acceleration_curve, ratio) * control * delta;
+            (context.acceleration, scale)
+        } else {
+            (context.deceleration, deceleration_scale)
+        };
+
+        let speed = step_toward(
+            Vector3::new(speed, 0., 0.),
+            Vector3::new(target_speed, 0., 0.),
+            rate,
+            scale,
+        )
+        .x;
+
+        direction * speed
+    };
+
+    Vector3::new(horizontal.x, velocity.y, horizontal.z)
+}
+
+/// `move_toward()` by `rate * scale`, a rate of 0 jumps straight to `to`
+fn step_toward(from: Vector3, to: Vector3, rate: f32, scale: f32) -> Vector3 {
+    if rate <= 0. {
+        return to;
+    }
+
+    from.move_toward(to, rate * scale)
+}
+
+fn curve_factor(curve: &Option<Gd<Curve>>, ratio: f32) -> f32 {
+    curve
+        .as_ref()
+        .map_or(1., |curve| curve.sample(ratio.clamp(0., 1.)))
+}
+
+pub fn jump_pressed(input: &InputFrame) -> bool {
+    input.jump_pressed
+}
+
+/// Advances the coyote time and jump buffer timers, called once per
+/// physics frame by the movement machine
+pub fn update_jump_timers(context: &mut MovementContext, input: &InputFrame, delta: f32) {
+    let on_floor = context.get_node(context.player_node.clone()).is_on_floor();
+
+    context.time_since_grounded = match on_floor {
+        true => 0.,
+        false => context.time_since_grounded + delta,
+    };
+
+    context.time_since_jump_pressed = match jump_pressed(input) {
+        true => 0.,
+        false => context.time_since_jump_pressed + delta,
+    };
+}
+
+/// A jump was pressed within the buffer time while the player stood on
+/// the floor within the coyote time
+pub fn should_jump(context: &MovementContext) -> bool {
+    context.time_since_jump_pressed <= context.jump_buffer_time
+        && context.time_since_grounded <= context.coyote_time
+}
+
+/// Clears both timers so a jump is not triggered twice
+pub fn consume_jump(context: &mut MovementContext) {
+    context.time_since_grounded = f32::INFINITY;
+    context.time_since_jump_pressed = f32::INFINITY;
+}
+
+/// The dodge action was pressed and the dodge cooldown ran out
+pub fn can_dodge(context: &MovementContext, input: &InputFrame) -> bool {
+    context.dodge_cooldown_remaining <= 0. && input.dodge_pressed
+}
+
+/// Counts down the dodge cooldown, called once per physics frame by the
+/// movement machine
+pub fn update_dodge_cooldown(context: &mut MovementContext, delta: f32) {
+    context.dodge_cooldown_remaining = (context.dodge_cooldown_remaining - delta).max(0.);
+}
+
+pub fn crouch_pressed(input: &InputFrame) -> bool {
+    input.crouch_held
+}
+
+/// Whether the sprint action is held and the stamina allows sprinting
+pub fn can_sprint(context: &mut MovementContext, input: &InputFrame) -> bool {
+    let pressed = input.sprint_held;
+    if !pressed {
+        context.sprint_locked = false;
+    }
+
+    pressed && !context.sprint_locked && context.stamina > 0.
+}
+
+/// Adds `amount` to the stamina pool and emits the stamina signals,
+/// returns false once the pool is empty
+pub fn change_stamina(context: &mut Gd<MovementContext>, amount: f32) -> bool {
+    let (previous, stamina, max_stamina) = {
+        let mut context = context.bind_mut();
+        let previous = context.stamina;
+        context.stamina = (context.stamina + amount).clamp(0., context.max_stamina);
+
+        if context.stamina == 0. {
+            context.sprint_locked = true;
+        }
+
+        (previous, context.stamina, context.max_stamina)
+    };
+
+    if stamina != previous {
+        context
+            .signals()
+            .stamina_changed()
+            .emit(stamina, max_stamina);
+
+        if stamina == 0. {
+            context.signals().stamina_depleted().emit();
+        }
+    }
+
+    stamina > 0.
+}
+
+/// Refills the stamina pool by the regen rate of the context
+pub fn regen_stamina(context: &mut Gd<MovementContext>, delta: f32) {
+    let (stamina, max_stamina, regen) = {
+        let context = context.bind();
+        (context.stamina, context.max_stamina, context.stamina_regen)
+    };
+
+    if stamina < max_stamina {
+        change_stamina(context, regen * delta);
+    }
+}
+
+/// Applies the slope and floor snap settings of the context to the player
+pub fn configure_floor(context: &MovementContext) {
+    let mut player = context.get_node(context.player_node.clone());
+
+    player.set_floor_max_angle(context.max_slope_angle.to_radians());
+    player.set_floor_snap_length(context.floor_snap_length);
+    player.set_floor_stop_on_slope(true);
+    player.set_floor_constant_speed(true);
+
+    // NOTE: move_and_slide() carries the player along with the platform it
+    // stands on, leaving it adds the platform velocity to the player
+    player.set_platform_on_leave(match context.keep_platform_momentum {
+        true => PlatformOnLeave::ADD_VELOCITY,
+        false => PlatformOnLeave::DO_NOTHING,
+    });
+}
+
+/// Turns the player model with the rotating platform it stands on, called
+/// once per physics frame by the movement machine
+pub fn follow_platform_rotation(context: &MovementContext, delta: f32) {
+    let player = context.get_node(context.player_node.clone());
+    if !player.is_on_floor() {
+        return;
+    }
+
+    let angular_velocity = player.get_platform_angular_velocity();
+    if angular_velocity.y == 0. {
+        return;
+    }
+
+    let mut player_scene = context.get_node(context.player_scene_node.clone());
+    player_scene.rotate_y(angular_velocity.y * delta);
+}
+
+/// Moves the player on top of an obstacle it walked into if the obstacle is
+/// at most `step_height` high and has a walkable top, `motion` is the
+/// horizontal movement of this frame
+pub fn step_up(
+    context: &MovementContext,
+    player: &mut Gd<CharacterBody3D>,
+    motion: Vector3,
+) -> bool {
+    let motion = Vector3::new(motion.x, 0., motion.z);
+    if context.step_height <= 0. || motion == Vector3::ZERO || !player.is_on_wall() {
+        return false;
+    }
+
+    let up = Vector3::UP * context.step_height;
+    let from = player.get_global_transform();
+
+    // Room above the player and above the obstacle
+    if player.test_move(from, up) {
+        return false;
+    }
+
+    let raised = from.translated(up);
+    if player.test_move(raised, motion) {
+        return false;
+    }
+
+    let ahead = raised.translated(motion);
+    let collision = KinematicCollision3D::new_gd();
+    if !player.test_move_ex(ahead, -up).collision(&collision).done() {
+        return false;
+    }
+
+    if collision.get_normal().angle_to(Vector3::UP) > player.get_floor_max_angle() {
+        return false;
+    }
+
+    player.set_global_transform(ahead.translated(collision.get_travel()));
+    true
+}
+
+/// Keeps the player from climbing slopes steeper than the max slope angle
+/// and limits the speed it slides down at
+pub fn slide_down_steep_slope(
+    context: &MovementContext,
+    player: &Gd<CharacterBody3D>,
+    velocity: Vector3,
+) -> Vector3 {
+    if !player.is_on_wall() {
+        return velocity;
+    }
+
+    // NOTE: Vertical walls and overhangs have no downhill direction
+    let normal = player.get_wall_normal();
+    if normal.y <= 0.01 {
+        return velocity;
+    }
+
+    let downhill = Vector3::DOWN.slide(normal).normalized();
+    let mut velocity = velocity.slide(normal);
+
+    let speed = velocity.dot(downhill);
+    if speed < 0. {
+        velocity -= downhill * speed;
+    } else if speed > context.steep_slope_slide_speed {
+        velocity -= downhill * (speed - context.steep_slope_slide_speed);
+    }
+
+    velocity
+}
+
+/// Finds the water volume the player is in, called once per physics frame
+/// by the movement machine
+pub fn update_water_volume(context: &mut MovementContext) {
+    let player = context.get_node(context.player_node.clone());
+    let Some(mut tree) = player.get_tree() else {
+        return;
+    };
+
+    context.water_volume = tree
+        .get_nodes_in_group(WATER_VOLUME_GROUP)
+        .iter_shared()
+        .filter_map(|node| node.try_cast::<WaterVolume>().ok())
+        .find(|volume| volume.bind().contains(&player));
+}
+
+/// How far `height` above the player origin is below the water surface,
+/// negative above the surface and None outside of water
+pub fn water_depth(context: &MovementContext, height: f32) -> Option<f32> {
+    let volume = context.water_volume.as_ref()?;
+    let player = context.get_node(context.player_node.clone());
+
+    Some(volume.bind().get_surface_height() - (player.get_global_position().y + height))
+}
+
+/// The chest of the player is underwater
+pub fn is_submerged(context: &MovementContext) -> bool {
+    water_depth(context, context.chest_height).is_some_and(|depth| depth > 0.)
+}
+
+/// Camera relative velocity while swimming, moving forward follows the
+/// camera pitch and the jump and crouch actions swim up and down
+pub fn swim_velocity(context: &MovementContext, input: &InputFrame) -> Vector3 {
+    let speed = context.movement_speed * context.swim_speed_multiplier;
+
+    let mut velocity = input_velocity(context, input) * context.swim_speed_multiplier;
+    velocity.y = input.forward_axis * input.view_pitch * speed;
+
+    if input.jump_held {
+        velocity.y = speed;
+    } else if crouch_pressed(input) {
+        velocity.y = -speed;
+    }
+
+    velocity.limit_length(Some(speed))
+}
+
+/// Uses up breath while the head is underwater and regains it above the
+/// surface, called once per physics frame by the movement machine
+pub fn update_breath(context: &mut Gd<MovementContext>, delta: f32) {
+    let (previous, breath, max_breath) = {
+        let mut context = context.bind_mut();
+        let underwater = water_depth(&context, context.head_height).is_some_and(|depth| depth > 0.);
+
+        let amount = match underwater {
+            true => -delta,
+            false => context.breath_regen * delta,
+        };
+
+        let previous = context.breath;
+        context.breath = (context.breath + amount).clamp(0., context.max_breath);
+
+        (previous, context.breath, context.max_breath)
+    };
+
+    if breath != previous {
+        context.signals().breath_changed().emit(breath, max_breath);
+
+        if breath == 0. {
+            context.signals().breath_depleted().emit();
+        }
+    }
+}
+
+/// Horizontal direction the player model faces
+pub fn forward_direction(context: &MovementContext) -> Vector3 {
+    let player_scene = context.get_node(context.player_scene_node.clone());
+    let forward = player_scene.get_global_transform().basis.col_c();
+
+    Vector3::new(forward.x, 0., forward.z).normalized()
+}
+
+/// Casts a ray that ignores the player, returns the hit and the collider
+fn cast_ray(
+    player: &Gd<CharacterBody3D>,
+    from: Vector3,
+    to: Vector3,
+) -> Option<(WallHit, Gd<Object>)> {
+    let mut space_state = player
+        .get_world_3d()
+        .and_then(|mut world_3d| world_3d.get_direct_space_state())?;
+
+    let mut excludes: Array<Rid> = Array::new();
+    excludes.push(player.get_rid());
+
+    let mut query = PhysicsRayQueryParameters3D::create(from, to)?;
+    query.set_exclude(&excludes);
+
+    let result = space_state.intersect_ray(&query);
+    let hit = WallHit {
+        position: result.get("position")?.try_to().ok()?,
+        normal: result.get("normal")?.try_to().ok()?,
+    };
+    let collider = result.get("collider")?.try_to().ok()?;
+
+    Some((hit, collider))
+}
+
+/// Climbable surfaces are in the climbable group or on one of the
+/// climbable physics layers of the context
+fn is_climbable(context: &MovementContext, collider: Gd<Object>) -> bool {
+    let in_group = !context.climbable_group.is_empty()
+        && collider
+            .clone()
+            .try_cast::<Node>()
+            .is_ok_and(|node| node.is_in_group(context.climbable_group.arg()));
+
+    let on_layer = collider
+        .try_cast::<CollisionObject3D>()
+        .is_ok_and(|object| object.get_collision_layer() & context.climbable_layers != 0);
+
+    in_group || on_layer
+}
+
+/// Climbable surface in front of the chest of the player
+pub fn climbable_wall(context: &MovementContext) -> Option<WallHit> {
+    let player = context.get_node(context.player_node.clone());
+    let from = player.get_global_position() + Vector3::UP * context.chest_height;
+    let to = from + forward_direction(context) * context.wall_reach;
+
+    cast_ray(&player, from, to)
+        .filter(|(_, collider)| is_climbable(context, collider.clone()))
+        .map(|(hit, _)| hit)
+}
+
+/// The player moves into a climbable wall in front of it
+pub fn can_climb(context: &MovementContext, input: &InputFrame) -> bool {
+    climbable_wall(context).is_some_and(|wall| input_velocity(context, input).dot(wall.normal) < 0.)
+}
+
+/// Walkable ledge in front of the player between its chest and
+/// `ledge_grab_height` above its head, the position is the top of the ledge
+/// and the normal the one of the wall below it
+pub fn find_ledge(context: &MovementContext) -> Option<WallHit> {
+    let player = context.get_node(context.player_node.clone());
+    let origin = player.get_global_position();
+    let forward = forward_direction(context) * context.wall_reach;
+    let top = origin + Vector3::UP * (context.head_height + context.ledge_grab_height);
+
+    // Something in the way above the ledge
+    if cast_ray(&player, top, top + forward).is_some() {
+        return None;
+    }
+
+    let bottom = origin + Vector3::UP * context.chest_height + forward;
+    let (ledge, _) = cast_ray(&player, top + forward, bottom)?;
+    if ledge.normal.angle_to(Vector3::UP) > player.get_floor_max_angle() {
+        return None;
+    }
+
+    let probe = Vector3::new(origin.x, ledge.position.y - LEDGE_PROBE_DEPTH, origin.z);
+    let normal = cast_ray(&player, probe, probe + forward)
+        .map_or(-forward.normalized(), |(wall, _)| wall.normal);
+
+    Some(WallHit {
+        position: ledge.position,
+        normal,
+    })
+}
+
+/// Where the player stands after climbing onto `ledge`
+pub fn ledge_stand_position(context: &MovementContext, ledge: &WallHit) -> Vector3 {
+    let collision_shape = context.get_node(context.collision_shape_node.clone());
+    let radius = collision_shape
+        .get_shape()
+        .and_then(|shape| shape.try_cast::<CapsuleShape3D>().ok())
+        .map_or(0.5, |capsule| capsule.get_radius());
+
+    let inward = Vector3::new(-ledge.normal.x, 0., -ledge.normal.z).normalized();
+
+    ledge.position + inward * (radius + STAND_CHECK_MARGIN)
+}
+
+/// Whether the collider of the player fits at `position`
+pub fn has_room_to_stand(context: &MovementContext, position: Vector3) -> bool {
+    let player = context.get_node(context.player_node.clone());
+    let collision_shape = context.get_node(context.collision_shape_node.clone());
+
+    let Some(shape) = collision_shape.get_shape() else {
+        return false;
+    };
+
+    let Some(mut space_state) = player
+        .get_world_3d()
+        .and_then(|mut world_3d| world_3d.get_direct_space_state())
+    else {
+        return false;
+    };
+
+    let origin = position + Vector3::UP * STAND_CHECK_MARGIN;
+    let transform = Transform3D::new(Basis::IDENTITY, origin) * collision_shape.get_transform();
+
+    let mut excludes: Array<Rid> = Array::new();
+    excludes.push(player.get_rid());
+
+    let mut query = PhysicsShapeQueryParameters3D::new_gd();
+    query.set_shape(&shape);
+    query.set_transform(transform);
+    query.set_exclude(&excludes);
+
+    space_state
+        .intersect_shape_ex(&query)
+        .max_results(1)
+        .done()
+        .is_empty()
+}
+
+/// Velocity along the wall from the movement actions, forward climbs up
+pub fn climb_velocity(context: &MovementContext, input: &InputFrame, wall: &WallHit) -> Vector3 {
+    let speed = context.movement_speed * context.climb_speed_multiplier;
+    let right = (-wall.normal).cross(Vector3::UP).normalized();
+    let up = wall.normal.cross(right).normalized();
+
+    let climb = climb_axis(input);
+    let strafe = input.strafe_axis;
+
+    (up * climb + right * strafe).limit_length(Some(1.)) * speed
+}
+
+/// Forward minus backward movement action strength
+pub fn climb_axis(input: &InputFrame) -> f32 {
+    input.forward_axis
+}
+
+/// Turns the player model towards the direction it moves in
+pub fn rotate_toward_velocity(context: &MovementContext, velocity: Vector3) {
+    let direction = Vector3::new(velocity.x, 0., velocity.z);
+
+    // Only rotate the model if there is movement
+    if direction == Vector3::ZERO {
+        return;
+    }
+
+    let mut player_scene = context.get_node(context.player_scene_node.clone());
+
+    let current_basis = player_scene.get_basis();
+    let target_basis = Basis::looking_at(direction, Vector3::UP, true);
+    let interpolated = current_basis.slerp(&target_basis, 0.2);
+    player_scene.set_basis(interpolated);
+}
+
+/// Vertical acceleration from the project gravity scaled by the context
+pub fn gravity(context: &MovementContext) -> f32 {
+    project_gravity().y * context.gravity_scale
+}
+
+/// Vertical speed needed to reach `jump_height` under `gravity()`
+pub fn jump_velocity(context: &MovementContext) -> f32 {
+    (2. * gravity(context).abs() * context.jump_height).sqrt()
+}
+
+/// Requests the AnimationTree state `name`, without an AnimationTree the
+/// animation is played on the AnimationPlayer, an empty name plays nothing
+pub fn play_animation(context: &MovementContext, name: &GString) {
+    if name.is_empty() {
+        return;
+    }
+
+    if let Some(mut animation_tree) = context.animation_tree_node.clone() {
+        let parameter = tree_parameter(&context.state_transition_node, "transition_request");
+        animation_tree.set(&parameter, &name.to_variant());
+        return;
+    }
+
+    let mut animator = context.get_node(context.animator.clone());
+    animator.play_ex().name(name.arg()).done();
+}
+
+/// Switches to the idle/walk/run blend space, without an AnimationTree
+/// `fallback` is played instead and an empty fallback stops the
+/// AnimationPlayer
+pub fn play_locomotion(context: &MovementContext, fallback: &GString) {
+    if context.animation_tree_node.is_some() {
+        play_animation(context, &context.locomotion_animation_name);
+    } else if fallback.is_empty() {
+        context.get_node(context.animator.clone()).stop();
+    } else {
+        play_animation(context, fallback);
+    }
+}
+
+fn tree_parameter(node: &GString, parameter: &str) -> StringName {
+    StringName::from(format!("parameters/{node}/{parameter}").as_str())
+}
+
+/// AnimationTree if there is one, the AnimationPlayer otherwise
+fn animation_mixer(context: &MovementContext) -> Gd<AnimationMixer> {
+    match context.animation_tree_node.clone() {
+        Some(animation_tree) => animation_tree.upcast(),
+        None => context.get_node(context.animator.clone()).upcast(),
+    }
+}
+
+/// Sets the root motion track and processes the animations on the physics
+/// frame so the root motion lines up with the movement
+pub fn configure_root_motion(context: &MovementContext) {
+    if !context.use_root_motion {
+        return;
+    }
+
+    let mut mixer = animation_mixer(context);
+    mixer.set_callback_mode_process(AnimationCallbackModeProcess::PHYSICS);
+
+    if !context.root_motion_track.is_empty() {
+        mixer.set_root_motion_track(&context.root_motion_track);
+    }
+}
+
+/// Replaces the horizontal velocity by the root motion of this frame when
+/// the context uses root motion, y is kept for gravity
+pub fn apply_root_motion(context: &MovementContext, velocity: Vector3, delta: f32) -> Vector3 {
+    if !context.use_root_motion || delta <= 0. {
+        return velocity;
+    }
+
+    let basis = context
+        .get_node(context.player_scene_node.clone())
+        .get_global_transform()
+        .basis;
+    let root_velocity = basis * animation_mixer(context).get_root_motion_position() / delta;
+
+    Vector3::new(root_velocity.x, velocity.y, root_velocity.z)
+}
+
+/// Activates the AnimationTree and applies the crossfade time of the context
+pub fn configure_animation_tree(context: &MovementContext) {
+    let Some(mut animation_tree) = context.animation_tree_node.clone() else {
+        return;
+    };
+
+    animation_tree.set_active(true);
+
+    let transition = animation_tree
+        .get_tree_root()
+        .and_then(|root| root.try_cast::<AnimationNodeBlendTree>().ok())
+        .and_then(|root| root.get_node(context.state_transition_node.arg()))
+        .and_then(|node| node.try_cast::<AnimationNodeTransition>().ok());
+
+    if let Some(mut transition) = transition {
+        transition.set_xfade_time(context.state_crossfade_time.into());
+    }
+}
+
+/// Feeds the velocity of the player into the locomotion blend space, called
+/// once per physics frame by the movement machine
+pub fn update_locomotion_blend(context: &mut MovementContext, input: &InputFrame, delta: f32) {
+    let Some(mut animation_tree) = context.animation_tree_node.clone() else {
+        return;
+    };
+
+    // NOTE: With root motion the velocity follows the blend space, so the
+    // blend space follows the movement input instead
+    let velocity = if context.use_root_motion {
+        input_velocity(context, input) * context.speed_multiplier
+    } else {
+        context.get_node(context.player_node.clone()).get_velocity()
+    } / context.movement_speed.max(f32::EPSILON);
+
+    let target = if context.locomotion_blend_2d {
+        // NOTE: The model faces +z, so its right is -x
+        let basis = context
+            .get_node(context.player_scene_node.clone())
+            .get_global_transform()
+            .basis;
+
+        Vector2::new(velocity.dot(-basis.col_a()), velocity.dot(basis.col_c()))
+    } else {
+        Vector2::new(Vector3::new(velocity.x, 0., velocity.z).length(), 0.)
+    };
+
+    context.locomotion_blend = if context.locomotion_blend_time <= 0. {
+        target
+    } else {
+        let weight = (delta / context.locomotion_blend_time).min(1.);
+        context.locomotion_blend.lerp(target, weight)
+    };
+
+    let blend_position = match context.locomotion_blend_2d {
+        true => context.locomotion_blend.to_variant(),
+        false => context.locomotion_blend.x.to_variant(),
+    };
+
+    let parameter = tree_parameter(&context.locomotion_blend_node, "blend_position");
+    animation_tree.set(&parameter, &blend_position);
+}
+
+/// Plays the AudioStreamPlayer3D at `path` relative to the player, an empty
+/// path plays nothing
+pub fn play_sound(context: &MovementContext, path: &NodePath) {
+    if path.is_empty() {
+        return;
+    }
+
+    let player = context.get_node(context.player_node.clone());
+    if let Some(mut sound) = player.try_get_node_as::<AudioStreamPlayer3D>(path) {
+        sound.play();
+    }
+}
@@ -5,6 +5,15 @@ pub enum MovementStates {
     #[default]
     Idle,
     Walking,
+    Sprinting,
+    Crouching,
+    Jumping,
+    Falling,
+    Landing,
+    Swimming,
+    Climbing,
+    LedgeHang,
+    Dodging,
 }
 
 impl Display for MovementStates {
@@ -0,0 +1,114 @@
+use fsm_macros::State;
+use godot::{builtin::Vector3, classes::InputEvent, obj::Gd};
+
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase},
+};
+
+use super::{
+    context::MovementContext,
+    motion::{
+        accelerate, apply_root_motion, can_dodge, can_sprint, change_stamina, input_velocity,
+        is_submerged, play_locomotion, rotate_toward_velocity, should_jump, step_up,
+    },
+    movement_states::MovementStates,
+};
+
+#[derive(Debug, State)]
+#[state(name = MovementStates::Sprinting)]
+pub struct Sprinting {
+    #[state(context)]
+    context: Gd<MovementContext>,
+    #[state(next_state)]
+    next_state: Option<MovementStates>,
+}
+
+impl State<GodotInput> for Sprinting {
+    fn transitions(&self) -> Vec<MovementStates> {
+        vec![
+            MovementStates::Walking,
+            MovementStates::Idle,
+            MovementStates::Jumping,
+            MovementStates::Falling,
+            MovementStates::Swimming,
+            MovementStates::Dodging,
+        ]
+    }
+
+    fn enter(&mut self) {
+        self.set_next_state(MovementStates::Sprinting);
+
+        let sprint_speed_multiplier = self.context.bind().sprint_speed_multiplier;
+        self.context.bind_mut().speed_multiplier = sprint_speed_multiplier;
+
+        let context = self.context.bind();
+        play_locomotion(&context, &context.sprint_animation_name);
+    }
+
+    fn input(&mut self, _event: Gd<InputEvent>) {}
+
+    fn process(&mut self, _delta: f32) {}
+
+    fn physics_process(&mut self, delta: f32) {
+        let input = self.context.bind().input;
+
+        if !can_sprint(&mut self.context.bind_mut(), &input) {
+            self.set_next_state(MovementStates::Walking);
+            return;
+        }
+
+        let drain = self.context.bind().stamina_drain;
+        if !change_stamina(&mut self.context, -drain * delta) {
+            // NOTE: Out of stamina, Walking keeps sprinting locked until
+            // the sprint action is released
+            self.set_next_state(MovementStates::Walking);
+            return;
+        }
+
+        let gd_context = self.context.clone();
+        let context = gd_context.bind();
+        let mut player = context.get_node(context.player_node.clone());
+
+        if is_submerged(&context) {
+            self.set_next_state(MovementStates::Swimming);
+            return;
+        }
+
+        if can_dodge(&context, &input) {
+            self.set_next_state(MovementStates::Dodging);
+            return;
+        }
+
+        if should_jump(&context) {
+            self.set_next_state(MovementStates::Jumping);
+            return;
+        }
+
+        let target = input_velocity(&context, &input) * context.sprint_speed_multiplier;
+        if target == Vector3::ZERO {
+            self.set_next_state(MovementStates::Idle);
+            return;
+        }
+
+        let mut velocity = accelerate(&context, player.get_velocity(), target, delta, false);
+        velocity.y = 0.;
+
+        player.set_velocity(apply_root_motion(&context, velocity, delta));
+        player.move_and_slide();
+        rotate_toward_velocity(&context, velocity);
+
+        if player.is_on_floor() {
+            step_up(&context, &mut player, velocity * delta);
+        }
+
+        if !player.is_on_floor() {
+            self.set_next_state(MovementStates::Falling);
+        }
+    }
+
+    fn exit(&mut self) {
+        self.set_next_state(MovementStates::Sprinting);
+        self.context.bind_mut().speed_multiplier = 1.;
+    }
+}
@@ -0,0 +1,124 @@
+use fsm_macros::State;
+use godot::{builtin::Vector3, classes::InputEvent, obj::Gd};
+
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase},
+};
+
+use super::{
+    context::MovementContext,
+    motion::{
+        accelerate, jump_pressed, play_animation, rotate_toward_velocity, swim_velocity,
+        water_depth,
+    },
+    movement_states::MovementStates,
+};
+
+/// How far the chest has to be above the surface before the player stands
+/// up again, keeps shallow water from toggling between states
+const WATER_EXIT_MARGIN: f32 = 0.1;
+
+/// Below this depth the player treads water and can jump out
+const SURFACE_MARGIN: f32 = 0.2;
+
+/// Keeps part of the falling speed when diving into the water
+const WATER_ENTRY_DAMPING: f32 = 0.3;
+
+#[derive(Debug, State)]
+#[state(name = MovementStates::Swimming)]
+pub struct Swimming {
+    #[state(context)]
+    context: Gd<MovementContext>,
+    #[state(next_state)]
+    next_state: Option<MovementStates>,
+    velocity: Vector3,
+}
+
+impl State<GodotInput> for Swimming {
+    fn transitions(&self) -> Vec<MovementStates> {
+        vec![
+            MovementStates::Idle,
+            MovementStates::Jumping,
+            MovementStates::Falling,
+        ]
+    }
+
+    fn enter(&mut self) {
+        self.set_next_state(MovementStates::Swimming);
+
+        let context = self.context.bind();
+        let player = context.get_node(context.player_node.clone());
+
+        self.velocity = player.get_velocity();
+        self.velocity.y *= WATER_ENTRY_DAMPING;
+
+        play_animation(&context, &context.swim_animation_name);
+    }
+
+    fn input(&mut self, _event: Gd<InputEvent>) {}
+
+    fn process(&mut self, _delta: f32) {}
+
+    fn physics_process(&mut self, delta: f32) {
+        let gd_context = self.context.clone();
+        let context = gd_context.bind();
+        let mut player = context.get_node(context.player_node.clone());
+
+        let Some(depth) = water_depth(&context, context.chest_height) else {
+            if player.is_on_floor() {
+                self.set_next_state(MovementStates::Idle);
+            } else {
+                self.set_next_state(MovementStates::Falling);
+            }
+
+            return;
+        };
+
+        if depth < -WATER_EXIT_MARGIN && player.is_on_floor() {
+            self.set_next_state(MovementStates::Idle);
+            return;
+        }
+
+        let input = context.input;
+        if depth < SURFACE_MARGIN && jump_pressed(&input) {
+            self.set_next_state(MovementStates::Jumping);
+            return;
+        }
+
+        let target = swim_velocity(&context, &input);
+        let buoyancy = context
+            .water_volume
+            .as_ref()
+            .map_or(0., |volume| volume.bind().buoyancy);
+
+        // NOTE: Without vertical input the buoyancy floats the chest to the
+        // surface, where the player treads water
+        let speed = context.movement_speed * context.swim_speed_multiplier;
+        let mut target_y = if target.y == 0. {
+            (depth * buoyancy).clamp(-speed, speed)
+        } else {
+            target.y
+        };
+
+        if depth <= 0. {
+            target_y = target_y.min(0.);
+        }
+
+        self.velocity = accelerate(&context, self.velocity, target, delta, false);
+        self.velocity.y = if context.acceleration <= 0. {
+            target_y
+        } else {
+            let max_change = context.acceleration * delta;
+            self.velocity.y + (target_y - self.velocity.y).clamp(-max_change, max_change)
+        };
+
+        player.set_velocity(self.velocity);
+        player.move_and_slide();
+        rotate_toward_velocity(&context, self.velocity);
+    }
+
+    fn exit(&mut self) {
+        self.set_next_state(MovementStates::Swimming);
+    }
+}
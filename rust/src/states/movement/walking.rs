@@ -1,134 +1,138 @@
-use std::sync::LazyLock;
+use fsm_macros::State;
+use godot::{builtin::Vector3, classes::InputEvent, obj::Gd};
 
-use godot::{
-    builtin::{Basis, Vector3},
-    classes::{Input, InputEvent},
-    obj::Gd,
+use crate::common::{
+    finite_state_machine::godot_adapter::GodotInput,
+    states::{State, StateBase},
 };
 
-use crate::{actions::Actions, common::states::State};
-
-use super::{context::MovementContext, movement_states::MovementStates};
-
-static ACTIONS: LazyLock<Actions> = LazyLock::new(Actions::default);
+use super::{
+    context::MovementContext,
+    input_recording::InputFrame,
+    motion::{
+        accelerate, apply_root_motion, can_climb, can_dodge, can_sprint, crouch_pressed,
+        input_velocity, is_submerged, play_locomotion, regen_stamina, rotate_toward_velocity,
+        should_jump, step_up,
+    },
+    movement_states::MovementStates,
+};
 
-#[derive(Debug)]
+#[derive(Debug, State)]
+#[state(name = MovementStates::Walking)]
 pub struct Walking {
-    #[allow(unused)]
+    #[state(context)]
     context: Gd<MovementContext>,
-    elapsed: f32,
+    #[state(next_state)]
     next_state: Option<MovementStates>,
     instant_velocity: Vector3,
 }
 
 impl Walking {
-    fn rotate_target_art(&mut self) {
-        // Only rotate the model if there is movement
-        if self.instant_velocity == Vector3::ZERO {
-            return;
-        }
-
-        let context = self.context.bind();
-        let mut player_scene = context.get_node(context.player_scene_node.clone());
-
-        let current_basis = player_scene.get_basis();
-        let target_basis = Basis::looking_at(self.instant_velocity, Vector3::UP, true);
-        let interpolated = current_basis.slerp(&target_basis, 0.2);
-        player_scene.set_basis(interpolated);
-    }
-
-    fn apply_ground_movement(&mut self, input: &Gd<Input>) {
+    /// Steers `velocity` towards the movement input, the result is the
+    /// velocity for this frame's `move_and_slide()`
+    fn apply_ground_movement(
+        &mut self,
+        input: &InputFrame,
+        velocity: Vector3,
+        delta: f32,
+    ) -> Vector3 {
         let gd_context = self.context.clone();
         let context = gd_context.bind();
 
-        let pivot = context.get_node(context.pivot_node.clone());
-        let mut player = context.get_node(context.player_node.clone());
-        let mut animator = context.get_node(context.animator.clone());
-
-        let context = self.context.clone();
-        let context = context.bind();
-        let pivot_y = pivot.get_global_rotation().y;
+        self.instant_velocity = input_velocity(&context, input);
 
-        let movement_vector = input
-            .get_vector(
-                ACTIONS.right,
-                ACTIONS.left,
-                ACTIONS.backward,
-                ACTIONS.forward,
-            )
-            .rotated(-pivot_y);
-
-        self.instant_velocity =
-            Vector3::new(movement_vector.x, 0., movement_vector.y) * context.movement_speed;
-
-        player.set_velocity(self.instant_velocity);
-        player.move_and_slide();
-
-        if self.instant_velocity != Vector3::ZERO {
-            animator
-                .play_ex()
-                .name(context.walking_animation_name.arg())
-                .done();
-        } else {
+        if self.instant_velocity == Vector3::ZERO {
             self.set_next_state(MovementStates::Idle);
         }
 
-        self.rotate_target_art();
-    }
-}
-
-impl State for Walking {
-    type StatesEnum = MovementStates;
-    type Context = Gd<MovementContext>;
-
-    fn new(context: Self::Context) -> Self {
-        Walking {
-            context,
-            elapsed: 0.,
-            next_state: None,
-            instant_velocity: Vector3::ZERO,
-        }
-    }
+        let mut velocity = accelerate(&context, velocity, self.instant_velocity, delta, false);
+        velocity.y = 0.;
 
-    fn get_state_name(&self) -> Self::StatesEnum {
-        MovementStates::Walking
-    }
+        rotate_toward_velocity(&context, velocity);
 
-    fn set_next_state(&mut self, state: Self::StatesEnum) {
-        self.next_state = Some(state);
+        velocity
     }
+}
 
-    fn get_next_state(&mut self) -> Option<Self::StatesEnum> {
-        self.next_state.clone()
+impl State<GodotInput> for Walking {
+    fn transitions(&self) -> Vec<MovementStates> {
+        vec![
+            MovementStates::Idle,
+            MovementStates::Sprinting,
+            MovementStates::Crouching,
+            MovementStates::Jumping,
+            MovementStates::Falling,
+            MovementStates::Swimming,
+            MovementStates::Climbing,
+            MovementStates::Dodging,
+        ]
     }
 
     fn enter(&mut self) {
         self.next_state = Some(MovementStates::Walking);
+
+        let context = self.context.bind();
+        play_locomotion(&context, &context.walking_animation_name);
     }
 
     fn input(&mut self, _event: Gd<InputEvent>) {}
 
     fn process(&mut self, _delta: f32) {}
 
-    fn physics_process(&mut self, _delta: f32) {
+    fn physics_process(&mut self, delta: f32) {
+        regen_stamina(&mut self.context, delta);
+
+        let input = self.context.bind().input;
+        let sprinting = can_sprint(&mut self.context.bind_mut(), &input);
+
         let gd_context = self.context.clone();
         let context = gd_context.bind();
         let mut player = context.get_node(context.player_node.clone());
 
-        let input = Input::singleton();
+        if is_submerged(&context) {
+            self.set_next_state(MovementStates::Swimming);
+            return;
+        }
+
+        if can_climb(&context, &input) {
+            self.set_next_state(MovementStates::Climbing);
+            return;
+        }
+
+        if can_dodge(&context, &input) {
+            self.set_next_state(MovementStates::Dodging);
+            return;
+        }
+
+        if should_jump(&context) {
+            self.set_next_state(MovementStates::Jumping);
+            return;
+        }
 
-        self.instant_velocity = Vector3::ZERO;
+        if player.is_on_floor() && crouch_pressed(&input) {
+            self.set_next_state(MovementStates::Crouching);
+            return;
+        }
 
-        // TODO: Move this to Jump state
-        // self.apply_jump(&input, node, delta);
+        if sprinting {
+            self.set_next_state(MovementStates::Sprinting);
+            return;
+        }
 
-        self.apply_ground_movement(&input);
-        player.set_velocity(self.instant_velocity);
+        let velocity = self.apply_ground_movement(&input, player.get_velocity(), delta);
+        player.set_velocity(apply_root_motion(&context, velocity, delta));
         player.move_and_slide();
+
+        if player.is_on_floor() {
+            step_up(&context, &mut player, velocity * delta);
+        }
+
+        if !player.is_on_floor() {
+            self.set_next_state(MovementStates::Falling);
+        }
     }
 
     fn exit(&mut self) {
-        self.elapsed = 0.;
         self.set_next_state(MovementStates::Walking);
     }
 }